@@ -0,0 +1,61 @@
+BEGIN;
+
+DROP TABLE IF EXISTS "tt01_power_factor_default_search_config";
+CREATE TABLE "tt01_power_factor_default_search_config" (
+    "sequence" NUMERIC,
+    "power" TEXT,
+    "vehicle_type_group" TEXT,
+    "policy_holder_type" TEXT,
+    "bonus_malus" TEXT,
+    "bonus_malus_scenario" TEXT,
+    CONSTRAINT "tt01_power_factor_default_search_config_key" UNIQUE ("power", "vehicle_type_group", "policy_holder_type", "bonus_malus", "bonus_malus_scenario")
+);
+
+COPY "tt01_power_factor_default_search_config" ("sequence", "power", "vehicle_type_group", "policy_holder_type", "bonus_malus", "bonus_malus_scenario") FROM stdin;
+1.0	VALUE	VALUE	VALUE	VALUE	VALUE
+2.0	VALUE	VALUE	NULL	VALUE	VALUE
+3.0	VALUE	VALUE	NULL	VALUE	NULL
+4.0	VALUE	NULL	NULL	VALUE	VALUE
+5.0	VALUE	VALUE	NULL	NULL	NULL
+6.0	VALUE	NULL	VALUE	NULL	NULL
+7.0	VALUE	NULL	NULL	NULL	NULL
+8.0	VALUE	VALUE	VALUE	VALUE	NULL
+9.0	VALUE	NULL	VALUE	VALUE	VALUE
+\.
+
+DROP TABLE IF EXISTS "tt_fuel_type_regitra_map";
+CREATE TABLE "tt_fuel_type_regitra_map" (
+    "valid_from" DATE,
+    "valid_to" DATE,
+    "fuel_type_from_regitra" TEXT,
+    "fuel_type" TEXT,
+    CONSTRAINT "tt_fuel_type_regitra_map_key" UNIQUE ("fuel_type_from_regitra", "fuel_type")
+);
+
+COPY "tt_fuel_type_regitra_map" ("valid_from", "valid_to", "fuel_type_from_regitra", "fuel_type") FROM stdin;
+\.
+
+DROP TABLE IF EXISTS "tt_combined_policy_discount";
+CREATE TABLE "tt_combined_policy_discount" (
+    "policy_type" TEXT,
+    "valid_from" DATE,
+    "valid_to" DATE,
+    "percent" NUMERIC,
+    CONSTRAINT "tt_combined_policy_discount_key" UNIQUE ("policy_type", "valid_from", "valid_to")
+);
+
+DROP TABLE IF EXISTS "tt02_depreciation_config_by_vehicle_age";
+CREATE TABLE "tt02_depreciation_config_by_vehicle_age" (
+    "age_from" NUMERIC,
+    "age_till" NUMERIC,
+    "read_only" TEXT,
+    "default_field_value" TEXT
+);
+
+COPY "tt02_depreciation_config_by_vehicle_age" ("age_from", "age_till", "read_only", "default_field_value") FROM stdin;
+0.0	4.0	Y	Y
+5.0	9.0	N	\N
+10.0	99.0	Y	N
+\.
+
+COMMIT;
@@ -0,0 +1,133 @@
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS bdt_tables (
+    ic TEXT PRIMARY KEY,
+    skip TEXT,
+    valid_from DATE,
+    valid_to DATE
+);
+CREATE TABLE IF NOT EXISTS bdt_table_names (
+    table_ic TEXT REFERENCES bdt_tables (ic) ON DELETE CASCADE,
+    lang TEXT,
+    name TEXT,
+    print_name TEXT,
+    short_print_name TEXT,
+    PRIMARY KEY (table_ic, lang)
+);
+CREATE TABLE IF NOT EXISTS bdt_columns (
+    table_ic TEXT REFERENCES bdt_tables (ic) ON DELETE CASCADE,
+    name TEXT,
+    title TEXT,
+    ref_code TEXT,
+    col_type TEXT,
+    codificator_id TEXT,
+    select_params TEXT,
+    sequence INTEGER,
+    is_key TEXT,
+    options TEXT,
+    skip TEXT,
+    PRIMARY KEY (table_ic, name)
+);
+DELETE FROM bdt_tables WHERE ic = 'TT01_POWER_FACTOR_DEFAULT_SEARCH_CONFIG';
+DELETE FROM bdt_tables WHERE ic = 'TT_FUEL_TYPE_REGITRA_MAP';
+DELETE FROM bdt_tables WHERE ic = 'TT_COMBINED_POLICY_DISCOUNT';
+DELETE FROM bdt_tables WHERE ic = 'TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE';
+
+COPY bdt_tables (ic, skip, valid_from, valid_to) FROM stdin;
+TT01_POWER_FACTOR_DEFAULT_SEARCH_CONFIG		\N	\N
+TT_FUEL_TYPE_REGITRA_MAP		\N	\N
+TT_COMBINED_POLICY_DISCOUNT	skipData	\N	\N
+TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE		\N	\N
+\.
+
+COPY bdt_table_names (table_ic, lang, name, print_name, short_print_name) FROM stdin;
+TT01_POWER_FACTOR_DEFAULT_SEARCH_CONFIG	EN	TT01 Power factor - search configuration		
+TT01_POWER_FACTOR_DEFAULT_SEARCH_CONFIG	LV	TT01 Jaudas faktors - meklēšanas konfigurācija		
+TT_FUEL_TYPE_REGITRA_MAP	EN	TT Fuel type Regitra map		
+TT_FUEL_TYPE_REGITRA_MAP	LV	TT Fuel type Regitra map		
+TT_COMBINED_POLICY_DISCOUNT	EN	TT Combined policy discounts		
+TT_COMBINED_POLICY_DISCOUNT	LV	TT Combined policy discounts		
+TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE	EN	TT02 Depreciation config by vehicle age		
+TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE	LV			
+\.
+
+COPY bdt_columns (table_ic, name, title, ref_code, col_type, codificator_id, select_params, sequence, is_key, options, skip) FROM stdin;
+TT01_POWER_FACTOR_DEFAULT_SEARCH_CONFIG	SEQUENCE	Sequence	NUM1	Num	\N	\N	1	N		
+TT01_POWER_FACTOR_DEFAULT_SEARCH_CONFIG	POWER	Power	CDF1_ID	Cdf	BDT_SEARCH_CONFIG_TYPE		2	Y		
+TT01_POWER_FACTOR_DEFAULT_SEARCH_CONFIG	VEHICLE_TYPE_GROUP	Vehicle type group	CDF2_ID	Cdf	BDT_SEARCH_CONFIG_TYPE		3	Y		
+TT01_POWER_FACTOR_DEFAULT_SEARCH_CONFIG	POLICY_HOLDER_TYPE	Policy holder type	CDF3_ID	Cdf	BDT_SEARCH_CONFIG_TYPE		4	Y		
+TT01_POWER_FACTOR_DEFAULT_SEARCH_CONFIG	BONUS_MALUS	Bonus malus	CDF4_ID	Cdf	BDT_SEARCH_CONFIG_TYPE		5	Y		
+TT01_POWER_FACTOR_DEFAULT_SEARCH_CONFIG	BONUS_MALUS_SCENARIO	Bonus malus scenario	CDF5_ID	Cdf	BDT_SEARCH_CONFIG_TYPE		6	Y		
+TT_FUEL_TYPE_REGITRA_MAP	VALID_FROM	Valid from	VALID_FROM	Date	\N	\N	1	N		
+TT_FUEL_TYPE_REGITRA_MAP	VALID_TO	Valid to	VALID_TO	Date	\N	\N	2	N		
+TT_FUEL_TYPE_REGITRA_MAP	FUEL_TYPE_FROM_REGITRA	Fuel type from Regitra	TEXT1	Text	\N	\N	3	Y		
+TT_FUEL_TYPE_REGITRA_MAP	FUEL_TYPE	Fuel type	CDF1_ID	Cdf	TT_FUEL_TYPE		4	Y		
+TT_COMBINED_POLICY_DISCOUNT	POLICY_TYPE	Policy type	CDF1_ID	Cdf	AGR_TYPE	icprefix=TT0	1	Y		
+TT_COMBINED_POLICY_DISCOUNT	VALID_FROM	Valid from	VALID_FROM	Date	\N	\N	2	Y		
+TT_COMBINED_POLICY_DISCOUNT	VALID_TO	Valid to	VALID_TO	Date	\N	\N	3	Y		
+TT_COMBINED_POLICY_DISCOUNT	PERCENT	%	NUM1	Num	\N	\N	4	N		
+TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE	AGE_FROM	Age from	NUM1	Num	\N	\N	1	N		
+TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE	AGE_TILL	Age till	NUM2	Num	\N	\N	2	N		
+TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE	READ_ONLY	Read only	CDF1_ID	Cdf	YN		3	N		
+TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE	DEFAULT_FIELD_VALUE	Default field value	CDF2_ID	Cdf	YN		4	N		
+\.
+
+DROP TABLE IF EXISTS "tt01_power_factor_default_search_config";
+CREATE TABLE "tt01_power_factor_default_search_config" (
+    "sequence" NUMERIC,
+    "power" TEXT,
+    "vehicle_type_group" TEXT,
+    "policy_holder_type" TEXT,
+    "bonus_malus" TEXT,
+    "bonus_malus_scenario" TEXT,
+    CONSTRAINT "tt01_power_factor_default_search_config_key" UNIQUE ("power", "vehicle_type_group", "policy_holder_type", "bonus_malus", "bonus_malus_scenario")
+);
+
+COPY "tt01_power_factor_default_search_config" ("sequence", "power", "vehicle_type_group", "policy_holder_type", "bonus_malus", "bonus_malus_scenario") FROM stdin;
+1.0	VALUE	VALUE	VALUE	VALUE	VALUE
+2.0	VALUE	VALUE	NULL	VALUE	VALUE
+3.0	VALUE	VALUE	NULL	VALUE	NULL
+4.0	VALUE	NULL	NULL	VALUE	VALUE
+5.0	VALUE	VALUE	NULL	NULL	NULL
+6.0	VALUE	NULL	VALUE	NULL	NULL
+7.0	VALUE	NULL	NULL	NULL	NULL
+8.0	VALUE	VALUE	VALUE	VALUE	NULL
+9.0	VALUE	NULL	VALUE	VALUE	VALUE
+\.
+
+DROP TABLE IF EXISTS "tt_fuel_type_regitra_map";
+CREATE TABLE "tt_fuel_type_regitra_map" (
+    "valid_from" DATE,
+    "valid_to" DATE,
+    "fuel_type_from_regitra" TEXT,
+    "fuel_type" TEXT,
+    CONSTRAINT "tt_fuel_type_regitra_map_key" UNIQUE ("fuel_type_from_regitra", "fuel_type")
+);
+
+COPY "tt_fuel_type_regitra_map" ("valid_from", "valid_to", "fuel_type_from_regitra", "fuel_type") FROM stdin;
+\.
+
+DROP TABLE IF EXISTS "tt_combined_policy_discount";
+CREATE TABLE "tt_combined_policy_discount" (
+    "policy_type" TEXT,
+    "valid_from" DATE,
+    "valid_to" DATE,
+    "percent" NUMERIC,
+    CONSTRAINT "tt_combined_policy_discount_key" UNIQUE ("policy_type", "valid_from", "valid_to")
+);
+
+DROP TABLE IF EXISTS "tt02_depreciation_config_by_vehicle_age";
+CREATE TABLE "tt02_depreciation_config_by_vehicle_age" (
+    "age_from" NUMERIC,
+    "age_till" NUMERIC,
+    "read_only" TEXT,
+    "default_field_value" TEXT
+);
+
+COPY "tt02_depreciation_config_by_vehicle_age" ("age_from", "age_till", "read_only", "default_field_value") FROM stdin;
+0.0	4.0	Y	Y
+5.0	9.0	N	\N
+10.0	99.0	Y	N
+\.
+
+COMMIT;
@@ -1,37 +1,282 @@
 use chrono::NaiveDate;
 pub use column_value::RowValues;
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 
+pub use skip::Skip;
+
 use crate::bdt::table_name::NameList;
 use crate::format::lv_date_format;
 
+/// Column types
 pub mod column_type;
+/// Typed values of data rows
 pub mod column_value;
+/// Deployment flags
+pub mod skip;
+/// Names of tables in languages
 pub mod table_name;
 
+/// Column of business table
 #[allow(dead_code)]
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Column {
-    pub skip: String,
+    /// Deployment flag of column and its data
+    pub skip: Skip,
+    /// Database id, if known
     pub id: Option<u64>,
+    /// Column name, like `CONFIG_TYPE`
     pub name: String,
+    /// Column title shown to users
     pub title: String,
+    /// Data field holding column values, like `NUM1` or `CDF1_ID`
     pub ref_code: String,
+    /// Type of values, from reference code
     pub col_type: column_type::ColumnType,
+    /// Position of column in table
     pub sequence: Option<u16>,
+    /// Column is part of the row key
     pub is_key: bool,
+    /// Options separated by `;`, like `scale=2`
     pub options: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Business data table with names, columns and data rows
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Bdt {
-    pub skip: String,
+    /// Deployment flag of table and its data
+    pub skip: Skip,
+    /// Identification code of table, like `TT_CONFIG`
     pub ic: String,
+    /// Table names in languages
     pub names: NameList,
+    /// Date table is valid from
     #[serde(with = "lv_date_format")]
     pub valid_from: Option<NaiveDate>,
+    /// Date table is valid to
     #[serde(with = "lv_date_format")]
     pub valid_to: Option<NaiveDate>,
+    /// Columns in table order
     pub columns: Vec<Column>,
+    /// Data rows
     pub data: Vec<RowValues>,
 }
+
+impl Column {
+    /// Number scale set as `scale=N` in column options, options are separated by `;`
+    pub fn scale(&self) -> Option<u32> {
+        self.options
+            .split(';')
+            .filter_map(|option| option.trim().strip_prefix("scale="))
+            .find_map(|scale| scale.trim().parse().ok())
+    }
+
+    /// Rounds (half away from zero, like Oracle ROUND) or pads number to column scale, if any
+    pub fn rescale(&self, num: Decimal) -> Decimal {
+        match self.scale() {
+            Some(scale) => {
+                let mut num =
+                    num.round_dp_with_strategy(scale, RoundingStrategy::MidpointAwayFromZero);
+                num.rescale(scale);
+                num
+            }
+            None => num,
+        }
+    }
+}
+
+impl Bdt {
+    /// Part of table which gets deployed, `None` if whole table is skipped. Skipped
+    /// columns are removed, `skipData` columns keep structure but lose their values,
+    /// skipped rows and data of `skipData` tables are dropped.
+    pub fn deployable(mut self) -> Option<Bdt> {
+        if self.skip == Skip::All {
+            return None;
+        }
+        if self.skip == Skip::Data {
+            self.data.clear();
+        }
+        self.data.retain(|row| row.skip.is_deploy());
+        let skipped: Vec<String> = self
+            .columns
+            .iter()
+            .filter(|column| !column.skip.is_deploy())
+            .map(|column| column.ref_code.clone())
+            .collect();
+        self.columns.retain(|column| column.skip != Skip::All);
+        for row in self.data.iter_mut() {
+            row.values
+                .retain(|value| !skipped.contains(&value.ref_code));
+        }
+        Some(self)
+    }
+
+    /// Applies column scale to NUM values
+    pub fn apply_scale(&mut self) {
+        for row in self.data.iter_mut() {
+            for value in row.values.iter_mut() {
+                if let column_value::ColumnValueType::Num(Some(num)) = &mut value.value {
+                    if let Some(column) = self.columns.iter().find(|c| c.ref_code == value.ref_code)
+                    {
+                        *num = column.rescale(*num);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drops trailing zeros of NUM values in columns without scale, like `30.0` written
+    /// for whole numbers by versions keeping NUM values as floats
+    pub fn normalize_numbers(&mut self) {
+        for row in self.data.iter_mut() {
+            for value in row.values.iter_mut() {
+                if let column_value::ColumnValueType::Num(Some(num)) = &mut value.value {
+                    let column = self.columns.iter().find(|c| c.ref_code == value.ref_code);
+                    if column.and_then(Column::scale).is_none() {
+                        *num = num.normalize();
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::column_value::{ColumnValue, ColumnValueType};
+    use super::*;
+
+    fn column(options: &str) -> Column {
+        Column {
+            ref_code: "NUM1".to_string(),
+            options: options.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn value(ref_code: &str) -> ColumnValue {
+        ColumnValue::new(
+            ref_code.to_string(),
+            ref_code.to_string(),
+            ColumnValueType::Text("x".to_string()),
+        )
+    }
+
+    fn skip_table(skip: Skip) -> Bdt {
+        let columns = [Skip::Deploy, Skip::All, Skip::Data]
+            .iter()
+            .enumerate()
+            .map(|(i, &skip)| Column {
+                skip,
+                ref_code: format!("TEXT{}", i + 1),
+                ..Default::default()
+            })
+            .collect();
+        let data = [Skip::Deploy, Skip::All, Skip::Data]
+            .iter()
+            .map(|&skip| RowValues {
+                skip,
+                values: vec![value("TEXT1"), value("TEXT2"), value("TEXT3")],
+            })
+            .collect();
+        Bdt {
+            skip,
+            ic: "TT".to_string(),
+            names: NameList::new(Vec::new()),
+            valid_from: None,
+            valid_to: None,
+            columns,
+            data,
+        }
+    }
+
+    #[test]
+    fn deployable_honours_skip_flags() {
+        assert!(skip_table(Skip::All).deployable().is_none());
+        assert!(skip_table(Skip::Data).deployable().unwrap().data.is_empty());
+
+        let bdt = skip_table(Skip::Deploy).deployable().unwrap();
+        let columns: Vec<&str> = bdt.columns.iter().map(|c| c.ref_code.as_str()).collect();
+        assert_eq!(columns, vec!["TEXT1", "TEXT3"]);
+        assert_eq!(bdt.data.len(), 1);
+        let values: Vec<&str> = bdt.data[0]
+            .values
+            .iter()
+            .map(|v| v.ref_code.as_str())
+            .collect();
+        assert_eq!(values, vec!["TEXT1"]);
+    }
+
+    #[test]
+    fn skip_flags_in_json() -> Result<(), serde_json::Error> {
+        let row: RowValues = serde_json::from_str(r#"{"values": []}"#)?;
+        assert_eq!(row.skip, Skip::Deploy);
+        assert_eq!(serde_json::to_string(&row)?, r#"{"values":[]}"#);
+        let row: RowValues = serde_json::from_str(r#"{"skip": "skipData", "values": []}"#)?;
+        assert_eq!(row.skip, Skip::Data);
+        assert!(serde_json::from_str::<Skip>(r#""other""#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn scale_from_options() {
+        assert_eq!(column("").scale(), None);
+        assert_eq!(column("scale=2").scale(), Some(2));
+        assert_eq!(column("hidden; scale=4").scale(), Some(4));
+    }
+
+    #[test]
+    fn rescale_to_column_scale() {
+        let column = column("scale=2");
+        assert_eq!(column.rescale(Decimal::new(11, 1)).to_string(), "1.10");
+        assert_eq!(column.rescale(Decimal::new(1155, 3)).to_string(), "1.16");
+    }
+
+    #[test]
+    fn normalize_numbers_keeps_column_scale() {
+        let num = |ref_code: &str, num: Decimal| {
+            ColumnValue::new(
+                ref_code.to_string(),
+                ref_code.to_string(),
+                ColumnValueType::Num(Some(num)),
+            )
+        };
+        let mut scaled = column("scale=2");
+        scaled.ref_code = "NUM2".to_string();
+        let mut bdt = Bdt {
+            columns: vec![column(""), scaled],
+            data: vec![RowValues {
+                skip: Skip::Deploy,
+                values: vec![
+                    num("NUM1", Decimal::new(300, 1)),
+                    num("NUM2", Decimal::new(110, 2)),
+                ],
+            }],
+            ..skip_table(Skip::Deploy)
+        };
+        bdt.normalize_numbers();
+        let values: Vec<String> = bdt.data[0]
+            .values
+            .iter()
+            .map(|value| match &value.value {
+                ColumnValueType::Num(Some(num)) => num.to_string(),
+                _ => String::new(),
+            })
+            .collect();
+        assert_eq!(values, ["30", "1.10"]);
+    }
+
+    #[test]
+    fn num_values_are_exact_in_json() -> Result<(), serde_json::Error> {
+        let json = r#"{"name": "RATE", "ref_code": "NUM1", "value": {"Num": 1.15}}"#;
+        let value: ColumnValue = serde_json::from_str(json)?;
+        assert!(
+            matches!(value.value, ColumnValueType::Num(Some(num)) if num == Decimal::new(115, 2))
+        );
+        assert_eq!(
+            serde_json::to_string(&value)?,
+            r#"{"name":"RATE","ref_code":"NUM1","value":{"Num":1.15}}"#
+        );
+        Ok(())
+    }
+}
@@ -3,14 +3,21 @@ use serde::{Deserialize, Serialize};
 
 use crate::csv_adapter::csv_model::ColumnRow;
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+/// Type of column values, given by its reference code
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub enum ColumnType {
+    /// `VALID_FROM` and `VALID_TO`
     Date,
+    /// `TEXT<n>`
     #[default]
     Text,
+    /// `NUM<n>`
     Num,
+    /// `CDF<n>_ID`, code of classifier entry
     Cdf {
+        /// Classifier of entries
         codificator_id: String,
+        /// Parameters selecting classifier entries
         select_params: String,
     },
 }
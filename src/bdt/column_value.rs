@@ -1,27 +1,42 @@
+use std::error::Error;
+use std::fmt;
+
 use chrono::NaiveDate;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-use crate::bdt::Column;
+use crate::bdt::{Column, Skip};
 use crate::csv_adapter::csv_model::DataRow;
 use crate::format::lv_date_format;
 
+/// Value of data field, by column type
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ColumnValueType {
+    /// Date, if any
     #[serde(with = "lv_date_format")]
     Date(Option<NaiveDate>),
+    /// Classifier entry code, empty if none
     Cdf(String),
-    Num(Option<f64>),
+    /// Number, if any
+    #[serde(with = "rust_decimal::serde::arbitrary_precision_option")]
+    Num(Option<Decimal>),
+    /// Text, empty if none
     Text(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Value of row in column
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ColumnValue {
+    /// Column name
     pub name: String,
+    /// Reference code of column
     pub ref_code: String,
+    /// Typed value
     pub value: ColumnValueType,
 }
 
 impl ColumnValue {
+    /// Value of column with name and reference code
     pub fn new(name: String, ref_code: String, value: ColumnValueType) -> ColumnValue {
         ColumnValue {
             name,
@@ -31,30 +46,55 @@ impl ColumnValue {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Row has value in column, by reference code, which table does not define
+#[derive(Debug)]
+pub struct UndefinedColumn(pub String);
+
+impl fmt::Display for UndefinedColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "data in undefined column {}", self.0)
+    }
+}
+
+impl Error for UndefinedColumn {}
+
+/// Data row, values by column
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct RowValues {
+    /// Deployment flag of row
+    #[serde(default, skip_serializing_if = "Skip::is_deploy")]
+    pub skip: Skip,
+    /// Values of columns having one
     pub values: Vec<ColumnValue>,
 }
 
 impl RowValues {
+    /// Row without values
     pub fn new() -> RowValues {
-        RowValues { values: Vec::new() }
+        RowValues::default()
     }
 
+    /// Adds value of column
     pub fn push(&mut self, value: ColumnValue) {
         self.values.push(value)
     }
 
-    #[allow(dead_code)]
-    pub fn len(&mut self) -> usize {
+    /// Count of values
+    pub fn len(&self) -> usize {
         self.values.len()
     }
 
-    #[allow(dead_code)]
-    pub fn get(&mut self, index: usize) -> Option<&ColumnValue> {
+    /// Row has no values
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Value at index
+    pub fn get(&self, index: usize) -> Option<&ColumnValue> {
         self.values.get(index)
     }
 
+    /// Date in column by reference code, none unless date value
     pub fn get_by_ref_as_date(&self, column_ref: &str) -> Option<NaiveDate> {
         let value = self
             .values
@@ -69,7 +109,8 @@ impl RowValues {
         date_value
     }
 
-    pub fn get_by_ref_as_num(&self, column_ref: &str) -> Option<f64> {
+    /// Number in column by reference code, none unless number value
+    pub fn get_by_ref_as_num(&self, column_ref: &str) -> Option<Decimal> {
         let value = self
             .values
             .iter()
@@ -83,6 +124,7 @@ impl RowValues {
         num_value
     }
 
+    /// Text in column by reference code, empty unless text value
     pub fn get_by_ref_as_text(&self, column_ref: &str) -> String {
         let value = self
             .values
@@ -97,6 +139,7 @@ impl RowValues {
         text_value
     }
 
+    /// Classifier code in column by reference code, empty unless classifier value
     pub fn get_by_ref_as_cdf(&self, column_ref: &str) -> String {
         let value = self
             .values
@@ -111,157 +154,159 @@ impl RowValues {
         cdf_value
     }
 
-    pub fn from_data_row(columns: &[Column], row: &DataRow) -> RowValues {
+    /// Row values of data CSV or Oracle row, failing for data in columns table does not define
+    pub fn from_data_row(columns: &[Column], row: &DataRow) -> Result<RowValues, UndefinedColumn> {
         let mut values = RowValues::new();
+        values.skip = row.skip;
         if let Some(column_value) =
-            RowValues::check_and_add_date_field(columns, "VALID_FROM", row.valid_from)
+            RowValues::check_and_add_date_field(columns, "VALID_FROM", row.valid_from)?
         {
             values.push(column_value)
         };
         if let Some(column_value) =
-            RowValues::check_and_add_date_field(columns, "VALID_TO", row.valid_to)
+            RowValues::check_and_add_date_field(columns, "VALID_TO", row.valid_to)?
         {
             values.push(column_value)
         };
-        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM1", row.num1) {
+        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM1", row.num1)? {
             values.push(column_value)
         }
-        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM2", row.num2) {
+        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM2", row.num2)? {
             values.push(column_value)
         }
-        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM3", row.num3) {
+        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM3", row.num3)? {
             values.push(column_value)
         }
-        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM4", row.num4) {
+        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM4", row.num4)? {
             values.push(column_value)
         }
-        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM5", row.num5) {
+        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM5", row.num5)? {
             values.push(column_value)
         }
-        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM6", row.num6) {
+        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM6", row.num6)? {
             values.push(column_value)
         }
-        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM7", row.num7) {
+        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM7", row.num7)? {
             values.push(column_value)
         }
-        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM8", row.num8) {
+        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM8", row.num8)? {
             values.push(column_value)
         }
-        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM9", row.num9) {
+        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM9", row.num9)? {
             values.push(column_value)
         }
-        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM10", row.num10)
+        if let Some(column_value) = RowValues::check_and_add_num_field(columns, "NUM10", row.num10)?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_text_field(columns, "TEXT1", row.text1.clone())
+            RowValues::check_and_add_text_field(columns, "TEXT1", row.text1.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_text_field(columns, "TEXT2", row.text2.clone())
+            RowValues::check_and_add_text_field(columns, "TEXT2", row.text2.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_text_field(columns, "TEXT3", row.text3.clone())
+            RowValues::check_and_add_text_field(columns, "TEXT3", row.text3.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_text_field(columns, "TEXT4", row.text4.clone())
+            RowValues::check_and_add_text_field(columns, "TEXT4", row.text4.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_text_field(columns, "TEXT5", row.text5.clone())
+            RowValues::check_and_add_text_field(columns, "TEXT5", row.text5.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_cdf_field(columns, "CDF1_ID", row.cdf1.clone())
+            RowValues::check_and_add_cdf_field(columns, "CDF1_ID", row.cdf1.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_cdf_field(columns, "CDF2_ID", row.cdf2.clone())
+            RowValues::check_and_add_cdf_field(columns, "CDF2_ID", row.cdf2.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_cdf_field(columns, "CDF3_ID", row.cdf3.clone())
+            RowValues::check_and_add_cdf_field(columns, "CDF3_ID", row.cdf3.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_cdf_field(columns, "CDF4_ID", row.cdf4.clone())
+            RowValues::check_and_add_cdf_field(columns, "CDF4_ID", row.cdf4.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_cdf_field(columns, "CDF5_ID", row.cdf5.clone())
+            RowValues::check_and_add_cdf_field(columns, "CDF5_ID", row.cdf5.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_cdf_field(columns, "CDF6_ID", row.cdf6.clone())
+            RowValues::check_and_add_cdf_field(columns, "CDF6_ID", row.cdf6.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_cdf_field(columns, "CDF7_ID", row.cdf7.clone())
+            RowValues::check_and_add_cdf_field(columns, "CDF7_ID", row.cdf7.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_cdf_field(columns, "CDF8_ID", row.cdf8.clone())
+            RowValues::check_and_add_cdf_field(columns, "CDF8_ID", row.cdf8.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_cdf_field(columns, "CDF9_ID", row.cdf9.clone())
+            RowValues::check_and_add_cdf_field(columns, "CDF9_ID", row.cdf9.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_cdf_field(columns, "CDF10_ID", row.cdf10.clone())
+            RowValues::check_and_add_cdf_field(columns, "CDF10_ID", row.cdf10.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_cdf_field(columns, "CDF11_ID", row.cdf11.clone())
+            RowValues::check_and_add_cdf_field(columns, "CDF11_ID", row.cdf11.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_cdf_field(columns, "CDF12_ID", row.cdf12.clone())
+            RowValues::check_and_add_cdf_field(columns, "CDF12_ID", row.cdf12.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_cdf_field(columns, "CDF13_ID", row.cdf13.clone())
+            RowValues::check_and_add_cdf_field(columns, "CDF13_ID", row.cdf13.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_cdf_field(columns, "CDF14_ID", row.cdf14.clone())
+            RowValues::check_and_add_cdf_field(columns, "CDF14_ID", row.cdf14.clone())?
         {
             values.push(column_value)
         }
         if let Some(column_value) =
-            RowValues::check_and_add_cdf_field(columns, "CDF15_ID", row.cdf15.clone())
+            RowValues::check_and_add_cdf_field(columns, "CDF15_ID", row.cdf15.clone())?
         {
             values.push(column_value)
         }
-        values
+        Ok(values)
     }
 
     fn check_and_add_date_field(
         columns: &[Column],
         ref_code: &str,
         date: Option<NaiveDate>,
-    ) -> Option<ColumnValue> {
+    ) -> Result<Option<ColumnValue>, UndefinedColumn> {
         if date.is_some() {
             let find_col = columns.iter().find(|col| col.ref_code == ref_code);
             let column_value = match find_col {
@@ -270,38 +315,38 @@ impl RowValues {
                     value.ref_code.clone(),
                     ColumnValueType::Date(date),
                 ),
-                None => panic!("CSV has data undefined column {}", ref_code),
+                None => return Err(UndefinedColumn(ref_code.to_string())),
             };
-            return Some(column_value);
+            return Ok(Some(column_value));
         }
-        None
+        Ok(None)
     }
 
     fn check_and_add_num_field(
         columns: &[Column],
         ref_code: &str,
-        num: Option<f64>,
-    ) -> Option<ColumnValue> {
+        num: Option<Decimal>,
+    ) -> Result<Option<ColumnValue>, UndefinedColumn> {
         if num.is_some() {
             let find_col = columns.iter().find(|col| col.ref_code == ref_code);
             let column_value = match find_col {
                 Some(value) => ColumnValue::new(
                     value.name.clone(),
                     value.ref_code.clone(),
-                    ColumnValueType::Num(num),
+                    ColumnValueType::Num(num.map(|num| value.rescale(num))),
                 ),
-                None => panic!("CSV has data undefined column {}", ref_code),
+                None => return Err(UndefinedColumn(ref_code.to_string())),
             };
-            return Some(column_value);
+            return Ok(Some(column_value));
         }
-        None
+        Ok(None)
     }
 
     fn check_and_add_text_field(
         columns: &[Column],
         ref_code: &str,
         str_data: String,
-    ) -> Option<ColumnValue> {
+    ) -> Result<Option<ColumnValue>, UndefinedColumn> {
         if !str_data.is_empty() {
             let find_col = columns.iter().find(|col| col.ref_code == ref_code);
             let column_value = match find_col {
@@ -310,18 +355,18 @@ impl RowValues {
                     value.ref_code.clone(),
                     ColumnValueType::Text(str_data),
                 ),
-                None => panic!("CSV has data undefined column {}", ref_code),
+                None => return Err(UndefinedColumn(ref_code.to_string())),
             };
-            return Some(column_value);
+            return Ok(Some(column_value));
         }
-        None
+        Ok(None)
     }
 
     fn check_and_add_cdf_field(
         columns: &[Column],
         ref_code: &str,
         str_data: String,
-    ) -> Option<ColumnValue> {
+    ) -> Result<Option<ColumnValue>, UndefinedColumn> {
         if !str_data.is_empty() {
             let find_col = columns.iter().find(|col| col.ref_code == ref_code);
             let column_value = match find_col {
@@ -330,10 +375,10 @@ impl RowValues {
                     value.ref_code.clone(),
                     ColumnValueType::Cdf(str_data),
                 ),
-                None => panic!("CSV has data undefined column {}", ref_code),
+                None => return Err(UndefinedColumn(ref_code.to_string())),
             };
-            return Some(column_value);
+            return Ok(Some(column_value));
         }
-        None
+        Ok(None)
     }
 }
@@ -0,0 +1,40 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Deployment flag of table, column or data row, kept as in source files
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Skip {
+    /// Deployed, empty flag
+    #[default]
+    #[serde(rename = "")]
+    Deploy,
+    /// Not deployed at all
+    #[serde(rename = "skip")]
+    All,
+    /// Table or column structure is deployed without data, rows are skipped as a whole
+    #[serde(rename = "skipData")]
+    Data,
+}
+
+impl Skip {
+    /// Flag as written in source files
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Skip::Deploy => "",
+            Skip::All => "skip",
+            Skip::Data => "skipData",
+        }
+    }
+
+    /// Not skipped in any way
+    pub fn is_deploy(&self) -> bool {
+        *self == Skip::Deploy
+    }
+}
+
+impl fmt::Display for Skip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
@@ -2,16 +2,22 @@ use serde::{Deserialize, Serialize};
 
 use crate::l11n::language::Language;
 
+/// Table name in one language
 #[allow(dead_code)]
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct Name {
+    /// Language of name
     pub lang: Language,
+    /// Name
     pub name: String,
+    /// Name used in prints
     pub print_name: String,
+    /// Short name used in prints
     pub short_print_name: String,
 }
 
 impl Name {
+    /// Name in language
     pub fn new(lang: Language, name: String, print_name: String, short_print_name: String) -> Name {
         Name {
             lang,
@@ -22,20 +28,29 @@ impl Name {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Names of table, one per language
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct NameList {
     names: Vec<Name>,
 }
 
 impl NameList {
+    /// List of names
     pub fn new(names: Vec<Name>) -> Self {
         NameList { names }
     }
 
+    /// Adds name
     pub fn push(&mut self, name: Name) {
         self.names.push(name)
     }
 
+    /// Names in list order
+    pub fn names(&self) -> &[Name] {
+        &self.names
+    }
+
+    /// Name in language, if any
     pub fn get_table_name(&self, lang: Language) -> Option<Name> {
         self.names
             .clone()
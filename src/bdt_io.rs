@@ -0,0 +1,318 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::bdt::Bdt;
+use crate::csv_adapter::{CsvOptions, CsvSource, CsvWriter};
+use crate::format::date_policy::DatePolicy;
+use crate::format::number_locale::NumberLocale;
+use crate::json_adapter::JsonAdapter;
+use crate::liquibase_adapter::LiquibaseWriter;
+#[cfg(feature = "oracle")]
+use crate::oracle_adapter::OracleAdapter;
+#[cfg(feature = "parquet")]
+use crate::parquet_adapter::ParquetAdapter;
+use crate::postgres_adapter::PostgresAdapter;
+use crate::sql_adapter::{SqlOptions, SqlWriter};
+#[cfg(feature = "sqlite")]
+use crate::sqlite_adapter::SqliteAdapter;
+use crate::template::Templates;
+#[cfg(feature = "xlsx")]
+use crate::xlsx_adapter::XlsxAdapter;
+
+pub use self::row_filter::RowFilter;
+pub use self::table_filter::TableFilter;
+
+/// Row filter of `--where` expressions
+pub mod row_filter;
+/// Table filter of `--tables` items
+pub mod table_filter;
+
+/// Reads business tables from location, like CSV directory, JSON file or table mask
+pub trait BdtSource {
+    /// Reads tables at location
+    fn read(&self, location: &str) -> Result<Vec<Bdt>, Box<dyn Error>>;
+}
+
+/// Writes business tables to location, like output file or directory
+pub trait BdtSink {
+    /// Writes tables to location
+    fn write(&self, tables: Vec<Bdt>, location: &str) -> Result<(), Box<dyn Error>>;
+
+    /// Writes tables whose rows were selected by row filter, sinks deploying tables
+    /// use it to limit changes to selected rows
+    fn write_filtered(
+        &self,
+        tables: Vec<Bdt>,
+        location: &str,
+        _rows: &RowFilter,
+    ) -> Result<(), Box<dyn Error>> {
+        self.write(tables, location)
+    }
+
+    /// Written script replaces table data in database, so rows left out by row
+    /// filters get deleted
+    fn replaces_data(&self) -> bool {
+        false
+    }
+}
+
+/// Format and location given as `<format>:<location>`, e.g. `json:TT.json`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Endpoint {
+    /// Registered format name, lower case
+    pub format: String,
+    /// File, directory or table mask
+    pub location: String,
+}
+
+impl FromStr for Endpoint {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.split_once(':') {
+            Some((format, location)) if !format.is_empty() && !location.is_empty() => {
+                Ok(Endpoint {
+                    format: format.to_lowercase(),
+                    location: location.to_string(),
+                })
+            }
+            _ => Err(format!(
+                "'{}' is not in form <format>:<location>, like json:TT.json",
+                value
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.format, self.location)
+    }
+}
+
+/// Result of [`Registry::convert`]
+#[derive(Debug)]
+pub struct Conversion {
+    /// Count of written tables
+    pub written: usize,
+    /// Selection items which matched no table
+    pub unmatched: Vec<String>,
+}
+
+/// Sources and sinks by format name
+#[derive(Default)]
+pub struct Registry {
+    sources: Vec<(&'static str, Box<dyn BdtSource>)>,
+    sinks: Vec<(&'static str, Box<dyn BdtSink>)>,
+    normalize_numbers: bool,
+}
+
+impl Registry {
+    /// Built-in formats, numbers and dates are written with given locale and format,
+    /// script sinks render given templates
+    pub fn with_defaults(
+        numbers: NumberLocale,
+        date_format: &str,
+        templates: &Templates,
+    ) -> Registry {
+        let mut registry = Registry::default();
+        let csv = CsvSource {
+            options: CsvOptions {
+                numbers,
+                ..Default::default()
+            },
+        };
+        registry.register_source("csv", Box::new(csv));
+        registry.register_source("json", Box::new(JsonAdapter {}));
+        #[cfg(feature = "oracle")]
+        registry.register_source("oracle", Box::new(OracleAdapter {}));
+
+        registry.register_sink("csv", Box::new(CsvWriter::with_numbers(numbers)));
+        registry.register_sink("json", Box::new(JsonAdapter {}));
+        let options = SqlOptions {
+            dates: DatePolicy::new(vec![], date_format),
+            numbers,
+            templates: templates.clone(),
+            ..Default::default()
+        };
+        registry.register_sink("sql", Box::new(SqlWriter::new(options.clone())));
+        registry.register_sink("liquibase", Box::new(LiquibaseWriter::new(options)));
+        #[cfg(feature = "sqlite")]
+        registry.register_sink(
+            "sqlite",
+            Box::new(SqliteAdapter {
+                templates: templates.clone(),
+                ..Default::default()
+            }),
+        );
+        registry.register_sink(
+            "postgres",
+            Box::new(PostgresAdapter {
+                templates: templates.clone(),
+            }),
+        );
+        #[cfg(feature = "parquet")]
+        registry.register_sink("parquet", Box::new(ParquetAdapter {}));
+        #[cfg(feature = "xlsx")]
+        registry.register_sink("xlsx", Box::new(XlsxAdapter {}));
+        registry
+    }
+
+    /// Drops trailing zeros of read NUM values, see [`Bdt::normalize_numbers`]
+    pub fn with_normalized_numbers(mut self, normalize: bool) -> Self {
+        self.normalize_numbers = normalize;
+        self
+    }
+
+    /// Adds source, replacing one registered with the same name
+    pub fn register_source(&mut self, name: &'static str, source: Box<dyn BdtSource>) {
+        self.sources.retain(|(registered, _)| *registered != name);
+        self.sources.push((name, source));
+    }
+
+    /// Adds sink, replacing one registered with the same name
+    pub fn register_sink(&mut self, name: &'static str, sink: Box<dyn BdtSink>) {
+        self.sinks.retain(|(registered, _)| *registered != name);
+        self.sinks.push((name, sink));
+    }
+
+    /// Source registered for format
+    pub fn source(&self, format: &str) -> Result<&dyn BdtSource, Box<dyn Error>> {
+        self.sources
+            .iter()
+            .find(|(name, _)| *name == format)
+            .map(|(_, source)| source.as_ref())
+            .ok_or_else(|| unknown("source", format, self.sources.iter().map(|s| s.0)))
+    }
+
+    /// Sink registered for format
+    pub fn sink(&self, format: &str) -> Result<&dyn BdtSink, Box<dyn Error>> {
+        self.sinks
+            .iter()
+            .find(|(name, _)| *name == format)
+            .map(|(_, sink)| sink.as_ref())
+            .ok_or_else(|| unknown("sink", format, self.sinks.iter().map(|s| s.0)))
+    }
+
+    /// Reads tables from one endpoint and writes selected tables and rows to other. Sinks
+    /// which [replace data](BdtSink::replaces_data) delete rows left out by row filter.
+    pub fn convert(
+        &self,
+        from: &Endpoint,
+        to: &Endpoint,
+        filter: &TableFilter,
+        rows: &RowFilter,
+    ) -> Result<Conversion, Box<dyn Error>> {
+        let source = self.source(&from.format)?;
+        let sink = self.sink(&to.format)?;
+        let tables = source.read(&from.location)?;
+        let unmatched = filter.unmatched(tables.iter().map(|bdt| bdt.ic.as_str()));
+        let mut tables = rows.apply(filter.apply(tables))?;
+        if self.normalize_numbers {
+            tables.iter_mut().for_each(Bdt::normalize_numbers);
+        }
+        let written = tables.len();
+        sink.write_filtered(tables, &to.location, rows)?;
+        Ok(Conversion { written, unmatched })
+    }
+}
+
+fn unknown<'a>(kind: &str, format: &str, known: impl Iterator<Item = &'a str>) -> Box<dyn Error> {
+    let known: Vec<&str> = known.collect();
+    format!(
+        "unknown {} format '{}', expected one of: {}",
+        kind,
+        format,
+        known.join(", ")
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::sql_adapter::SqlMode;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Collect(Rc<RefCell<Vec<String>>>);
+
+    impl BdtSink for Collect {
+        fn write(&self, tables: Vec<Bdt>, _location: &str) -> Result<(), Box<dyn Error>> {
+            self.0
+                .borrow_mut()
+                .extend(tables.into_iter().map(|bdt| bdt.ic));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn parse_endpoint() {
+        assert_eq!(
+            "json:C:\\bdt\\TT.json".parse(),
+            Ok(Endpoint {
+                format: "json".to_string(),
+                location: "C:\\bdt\\TT.json".to_string(),
+            })
+        );
+        assert!("TT.json".parse::<Endpoint>().is_err());
+        assert!("json:".parse::<Endpoint>().is_err());
+    }
+
+    #[test]
+    fn convert_filtered_tables() -> Result<(), Box<dyn Error>> {
+        let written = Rc::new(RefCell::new(Vec::new()));
+        let mut registry =
+            Registry::with_defaults(NumberLocale::EN, "%d.%m.%Y", &Templates::default());
+        registry.register_sink("collect", Box::new(Collect(written.clone())));
+        let filter = TableFilter::new(vec!["TT_CONFIG,TT99".to_string()])?;
+        let conversion = registry.convert(
+            &"csv:./data/TT".parse()?,
+            &"collect:-".parse()?,
+            &filter,
+            &RowFilter::default(),
+        )?;
+        assert_eq!(conversion.written, 1);
+        assert_eq!(conversion.unmatched, vec!["TT99"]);
+        assert_eq!(*written.borrow(), vec!["TT_CONFIG"]);
+        Ok(())
+    }
+
+    #[test]
+    fn row_filter_limits_merge_to_selected_rows() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!("bdt_convert_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let script = dir.join("merge.sql");
+        let mut registry =
+            Registry::with_defaults(NumberLocale::EN, "%d.%m.%Y", &Templates::default());
+        let options = SqlOptions {
+            mode: SqlMode::Merge,
+            ..Default::default()
+        };
+        registry.register_sink("merge", Box::new(SqlWriter::new(options)));
+        assert!(registry.sink("sql")?.replaces_data());
+        assert!(!registry.sink("merge")?.replaces_data());
+        let rows = RowFilter::new(vec!["AGE_FROM < 5".to_string()])?;
+        registry.convert(
+            &"json:./data/TT/TT.json".parse()?,
+            &format!("merge:{}", script.display()).parse()?,
+            &TableFilter::new(vec!["TT02*".to_string()])?,
+            &rows,
+        )?;
+        let text = std::fs::read_to_string(&script)?;
+        std::fs::remove_dir_all(&dir)?;
+        assert!(text.contains("\n  AND t.NUM1 < 5\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_format() {
+        let registry = Registry::with_defaults(NumberLocale::EN, "%d.%m.%Y", &Templates::default());
+        let error = registry.sink("ods").err().unwrap().to_string();
+        assert!(error.starts_with("unknown sink format 'ods', expected one of: csv, json, sql"));
+    }
+}
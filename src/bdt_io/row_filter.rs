@@ -0,0 +1,523 @@
+use std::cmp::Ordering;
+use std::error::Error;
+
+use chrono::NaiveDate;
+use regex::Regex;
+use rust_decimal::Decimal;
+
+use crate::bdt::column_type::ColumnType;
+use crate::bdt::column_value::ColumnValueType;
+use crate::bdt::{Bdt, Column, RowValues};
+use crate::format::date_policy::DatePolicy;
+use crate::template::helpers::oracle_literal;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    fn parse(op: &str) -> Op {
+        match op {
+            "=" => Op::Eq,
+            "!=" | "<>" => Op::Ne,
+            "<" => Op::Lt,
+            "<=" => Op::Le,
+            ">" => Op::Gt,
+            _ => Op::Ge,
+        }
+    }
+
+    fn sql(&self) -> &'static str {
+        match self {
+            Op::Eq => "=",
+            Op::Ne => "<>",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+        }
+    }
+
+    fn holds(&self, ordering: Ordering) -> bool {
+        match self {
+            Op::Eq => ordering == Ordering::Equal,
+            Op::Ne => ordering != Ordering::Equal,
+            Op::Lt => ordering == Ordering::Less,
+            Op::Le => ordering != Ordering::Greater,
+            Op::Gt => ordering == Ordering::Greater,
+            Op::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Condition {
+    /// `<column> is [not] null`
+    Null { column: String, null: bool },
+    /// `<column> <op> <literal>`, literal is typed by column type
+    Compare {
+        column: String,
+        op: Op,
+        literal: String,
+    },
+    /// Row is valid at date, missing VALID_FROM or VALID_TO is open ended
+    ValidAt(NaiveDate),
+}
+
+/// Value of a column typed for comparison, `None` for null or empty value
+#[derive(Debug, PartialEq, PartialOrd)]
+enum Value {
+    Date(NaiveDate),
+    Num(Decimal),
+    Text(String),
+}
+
+impl Value {
+    fn literal(column: &Column, literal: &str) -> Result<Value, Box<dyn Error>> {
+        let invalid = |e: &dyn Error| format!("column {}: {}", column.name, e);
+        Ok(match column.col_type {
+            ColumnType::Date => Value::Date(
+                DatePolicy::default()
+                    .parse(literal)
+                    .map_err(|e| invalid(&e))?,
+            ),
+            ColumnType::Num => Value::Num(
+                literal
+                    .parse()
+                    .map_err(|_| format!("column {}: invalid number '{}'", column.name, literal))?,
+            ),
+            _ => Value::Text(literal.to_string()),
+        })
+    }
+
+    fn of(row: &RowValues, ref_code: &str) -> Option<Value> {
+        let value = row.values.iter().find(|value| value.ref_code == ref_code)?;
+        match &value.value {
+            ColumnValueType::Date(date) => date.map(Value::Date),
+            ColumnValueType::Num(num) => num.map(Value::Num),
+            ColumnValueType::Cdf(text) | ColumnValueType::Text(text) if !text.is_empty() => {
+                Some(Value::Text(text.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Condition {
+    fn parse(expression: &str) -> Result<Condition, Box<dyn Error>> {
+        let null = Regex::new(r"(?i)^(\w+)\s+is\s+(not\s+)?null$")?;
+        let compare = Regex::new(r"^(\w+)\s*(=|!=|<>|<=|>=|<|>)\s*(.+)$")?;
+        let valid_at = Regex::new(r"(?i)^valid_at\s*\(\s*'?([^')]*)'?\s*\)$")?;
+        let expression = expression.trim();
+        if let Some(captures) = valid_at.captures(expression) {
+            let date = DatePolicy::default().parse(&captures[1])?;
+            Ok(Condition::ValidAt(date))
+        } else if let Some(captures) = null.captures(expression) {
+            Ok(Condition::Null {
+                column: captures[1].to_string(),
+                null: captures.get(2).is_none(),
+            })
+        } else if let Some(captures) = compare.captures(expression) {
+            Ok(Condition::Compare {
+                column: captures[1].to_string(),
+                op: Op::parse(&captures[2]),
+                literal: unquote(captures[3].trim()),
+            })
+        } else {
+            Err(format!("invalid row filter '{}'", expression).into())
+        }
+    }
+
+    /// Columns the condition needs, tables without them are not filtered by it
+    fn columns(&self) -> Vec<&str> {
+        match self {
+            Condition::Null { column, .. } | Condition::Compare { column, .. } => vec![column],
+            Condition::ValidAt(_) => vec!["VALID_FROM", "VALID_TO"],
+        }
+    }
+
+    fn compile(&self, bdt: &Bdt) -> Result<Option<Test>, Box<dyn Error>> {
+        let find = |name: &str| find_column(bdt, name);
+        Ok(match self {
+            Condition::Null { column, null } => find(column).map(|column| Test::Null {
+                ref_code: column.ref_code.clone(),
+                null: *null,
+            }),
+            Condition::Compare {
+                column,
+                op,
+                literal,
+            } => match find(column) {
+                Some(column) => Some(Test::Compare {
+                    ref_code: column.ref_code.clone(),
+                    op: *op,
+                    value: Value::literal(column, literal)?,
+                }),
+                None => None,
+            },
+            Condition::ValidAt(date) => match (find("VALID_FROM"), find("VALID_TO")) {
+                (None, None) => None,
+                (from, to) => Some(Test::ValidAt {
+                    from: from.map(|column| column.ref_code.clone()),
+                    to: to.map(|column| column.ref_code.clone()),
+                    date: *date,
+                }),
+            },
+        })
+    }
+
+    /// Oracle condition on `agc_ctb_data` row aliased `alias`, `None` for tables without
+    /// the column. Classifier codes can only be compared with `=` and `!=`.
+    fn oracle(&self, bdt: &Bdt, alias: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let date = |date: &NaiveDate| format!("DATE '{}'", date.format("%Y-%m-%d"));
+        Ok(match self {
+            Condition::Null { column, null } => find_column(bdt, column).map(|column| {
+                let not = if *null { "" } else { "NOT " };
+                format!("{}.{} IS {}NULL", alias, column.ref_code, not)
+            }),
+            Condition::Compare {
+                column: name,
+                op,
+                literal,
+            } => match find_column(bdt, name) {
+                None => None,
+                Some(column) => {
+                    let value = match (Value::literal(column, literal)?, &column.col_type) {
+                        (Value::Date(value), _) => date(&value),
+                        (Value::Num(value), _) => value.to_string(),
+                        (Value::Text(code), ColumnType::Cdf { codificator_id, .. }) => {
+                            if !matches!(op, Op::Eq | Op::Ne) {
+                                return Err(format!(
+                                    "row filter on classifier column {} can only use = or != \
+                                     for deployment",
+                                    column.name
+                                )
+                                .into());
+                            }
+                            format!(
+                                "f.get_cdf_id({},{})",
+                                oracle_literal(codificator_id),
+                                oracle_literal(&code)
+                            )
+                        }
+                        (Value::Text(text), _) => oracle_literal(&text),
+                    };
+                    Some(format!(
+                        "{}.{} {} {}",
+                        alias,
+                        column.ref_code,
+                        op.sql(),
+                        value
+                    ))
+                }
+            },
+            Condition::ValidAt(day) => {
+                let open = |name: &str, op: &str| {
+                    find_column(bdt, name).map(|column| {
+                        format!(
+                            "({0}.{1} IS NULL OR {0}.{1} {2} {3})",
+                            alias,
+                            column.ref_code,
+                            op,
+                            date(day)
+                        )
+                    })
+                };
+                match (open("VALID_FROM", "<="), open("VALID_TO", ">=")) {
+                    (None, None) => None,
+                    (from, to) => {
+                        Some(from.into_iter().chain(to).collect::<Vec<_>>().join(" AND "))
+                    }
+                }
+            }
+        })
+    }
+}
+
+fn find_column<'a>(bdt: &'a Bdt, name: &str) -> Option<&'a Column> {
+    bdt.columns.iter().find(|column| {
+        column.name.eq_ignore_ascii_case(name) || column.ref_code.eq_ignore_ascii_case(name)
+    })
+}
+
+/// Condition bound to columns of one table
+enum Test {
+    Null {
+        ref_code: String,
+        null: bool,
+    },
+    Compare {
+        ref_code: String,
+        op: Op,
+        value: Value,
+    },
+    ValidAt {
+        from: Option<String>,
+        to: Option<String>,
+        date: NaiveDate,
+    },
+}
+
+impl Test {
+    fn matches(&self, row: &RowValues) -> bool {
+        let date_of = |ref_code: &Option<String>| match ref_code
+            .as_ref()
+            .and_then(|ref_code| Value::of(row, ref_code))
+        {
+            Some(Value::Date(date)) => Some(date),
+            _ => None,
+        };
+        match self {
+            Test::Null { ref_code, null } => Value::of(row, ref_code).is_none() == *null,
+            Test::Compare {
+                ref_code,
+                op,
+                value,
+            } => Value::of(row, ref_code)
+                .and_then(|row_value| row_value.partial_cmp(value))
+                .is_some_and(|ordering| op.holds(ordering)),
+            Test::ValidAt { from, to, date } => {
+                date_of(from).is_none_or(|from| from <= *date)
+                    && date_of(to).is_none_or(|to| *date <= to)
+            }
+        }
+    }
+}
+
+/// Strips quotes of `'text'` literal, doubled quotes inside are unescaped
+fn unquote(literal: &str) -> String {
+    match literal
+        .strip_prefix('\'')
+        .and_then(|literal| literal.strip_suffix('\''))
+    {
+        Some(text) => text.replace("''", "'"),
+        None => literal.to_string(),
+    }
+}
+
+/// Splits expression on `and` outside of quoted literals
+fn split_and(expression: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let words: Vec<&str> = expression.split(' ').collect();
+    for word in words {
+        if !quoted && word.eq_ignore_ascii_case("and") {
+            parts.push(current.trim().to_string());
+            current.clear();
+            continue;
+        }
+        quoted ^= word.matches('\'').count() % 2 == 1;
+        current.push_str(word);
+        current.push(' ');
+    }
+    parts.push(current.trim().to_string());
+    parts
+}
+
+/// Row filter of expressions which all must hold. Expressions are
+/// `<column> is [not] null`, `<column> <op> <literal>` with `=`, `!=`, `<>`, `<`, `<=`,
+/// `>`, `>=` and `valid_at(<date>)`, joined with `and`. Columns are found by name or
+/// reference code, literals are compared as dates, numbers or text by column type.
+/// Tables without a column are not filtered by its conditions.
+#[derive(Clone, Debug, Default)]
+pub struct RowFilter {
+    conditions: Vec<Condition>,
+}
+
+impl RowFilter {
+    /// Parses expressions, fails on unknown syntax
+    pub fn new(expressions: Vec<String>) -> Result<RowFilter, Box<dyn Error>> {
+        let mut conditions = Vec::new();
+        for expression in &expressions {
+            for part in split_and(expression) {
+                conditions.push(Condition::parse(&part)?);
+            }
+        }
+        Ok(RowFilter { conditions })
+    }
+
+    /// Filter has no conditions, all rows are kept
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    /// Conditions of row filter on table columns as Oracle SQL on `agc_ctb_data` rows
+    /// aliased `alias`, so that deployment touches only rows the filter selects
+    pub(crate) fn oracle_conditions(
+        &self,
+        bdt: &Bdt,
+        alias: &str,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut conditions = Vec::new();
+        for condition in &self.conditions {
+            conditions.extend(condition.oracle(bdt, alias)?);
+        }
+        Ok(conditions)
+    }
+
+    /// Keeps matching rows, fails on literals not matching column type and on columns
+    /// missing in all tables
+    pub fn apply(&self, mut tables: Vec<Bdt>) -> Result<Vec<Bdt>, Box<dyn Error>> {
+        for condition in &self.conditions {
+            let known = condition.columns().iter().any(|name| {
+                tables.iter().flat_map(|bdt| &bdt.columns).any(|column| {
+                    column.name.eq_ignore_ascii_case(name)
+                        || column.ref_code.eq_ignore_ascii_case(name)
+                })
+            });
+            if !known && !tables.is_empty() {
+                return Err(format!(
+                    "row filter column {} is not in any table",
+                    condition.columns().join(" or ")
+                )
+                .into());
+            }
+        }
+        for bdt in tables.iter_mut() {
+            self.retain(bdt)?;
+        }
+        Ok(tables)
+    }
+
+    /// Keeps matching rows of one table, conditions on columns it has not are ignored
+    pub(crate) fn retain(&self, bdt: &mut Bdt) -> Result<(), Box<dyn Error>> {
+        let mut tests = Vec::new();
+        for condition in &self.conditions {
+            if let Some(test) = condition.compile(bdt)? {
+                tests.push(test);
+            }
+        }
+        bdt.data
+            .retain(|row| tests.iter().all(|test| test.matches(row)));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_adapter::JsonAdapter;
+
+    use super::*;
+
+    fn rows(expressions: &[&str]) -> Result<Vec<(String, usize)>, Box<dyn Error>> {
+        let filter = RowFilter::new(expressions.iter().map(|e| e.to_string()).collect())?;
+        let tables = filter.apply(JsonAdapter::read_bdt("./data/TT/TT.json")?)?;
+        Ok(tables
+            .into_iter()
+            .filter(|bdt| !bdt.data.is_empty())
+            .map(|bdt| (bdt.ic, bdt.data.len()))
+            .collect())
+    }
+
+    fn count(expressions: &[&str], ic: &str) -> usize {
+        rows(expressions)
+            .unwrap()
+            .into_iter()
+            .find(|(table, _)| table == ic)
+            .map_or(0, |(_, count)| count)
+    }
+
+    #[test]
+    fn parse_conditions() -> Result<(), Box<dyn Error>> {
+        let filter = RowFilter::new(vec![
+            "VALID_TO is null AND CONFIG_TYPE = 'GC_SERIES'".to_string(),
+            "valid_at(2024-01-01)".to_string(),
+        ])?;
+        assert_eq!(
+            filter.conditions,
+            vec![
+                Condition::Null {
+                    column: "VALID_TO".to_string(),
+                    null: true
+                },
+                Condition::Compare {
+                    column: "CONFIG_TYPE".to_string(),
+                    op: Op::Eq,
+                    literal: "GC_SERIES".to_string()
+                },
+                Condition::ValidAt(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
+            ]
+        );
+        assert_eq!(
+            split_and("TEXT1 = 'salt and pepper' and NUM1 > 1"),
+            vec!["TEXT1 = 'salt and pepper'", "NUM1 > 1"]
+        );
+        assert!(RowFilter::new(vec!["CONFIG_TYPE like 'GC%'".to_string()]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn filter_by_text_and_null() {
+        let total = count(&[], "TT_CONFIG");
+        assert_eq!(count(&["CONFIG_TYPE = 'GC_SERIES'"], "TT_CONFIG"), 1);
+        assert_eq!(
+            count(&["CONFIG_TYPE != 'GC_SERIES'"], "TT_CONFIG"),
+            total - 1
+        );
+        assert_eq!(count(&["VALID_TO is not null"], "TT_CONFIG"), 1);
+        assert_eq!(count(&["valid_to IS NULL"], "TT_CONFIG"), total - 1);
+    }
+
+    #[test]
+    fn filter_numbers_by_value() {
+        let ic = "TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE";
+        // 4 and 9 would not be less than 10 compared as text
+        assert_eq!(count(&["AGE_TILL < 10"], ic), 2);
+        assert_eq!(count(&["AGE_FROM >= 5", "NUM2 <= 9.0"], ic), 1);
+        assert!(rows(&["AGE_FROM > ten"]).is_err());
+    }
+
+    #[test]
+    fn filter_valid_at() -> Result<(), Box<dyn Error>> {
+        let mut tables = JsonAdapter::read_bdt("./data/TT/TT.json")?;
+        tables.retain(|bdt| bdt.ic == "TT_CONFIG");
+        let total = tables[0].data.len();
+        let dated: Vec<Option<NaiveDate>> = tables[0]
+            .data
+            .iter()
+            .map(|row| row.get_by_ref_as_date("VALID_FROM"))
+            .collect();
+        let filter = RowFilter::new(vec!["valid_at(01.01.2000)".to_string()])?;
+        let tables = filter.apply(tables)?;
+        let undated = dated.iter().filter(|date| date.is_none()).count();
+        assert!(undated < total);
+        assert_eq!(tables[0].data.len(), undated);
+        Ok(())
+    }
+
+    #[test]
+    fn oracle_conditions() -> Result<(), Box<dyn Error>> {
+        let tables = JsonAdapter::read_bdt("./data/TT/TT.json")?;
+        let config = tables.iter().find(|bdt| bdt.ic == "TT_CONFIG").unwrap();
+        let filter = RowFilter::new(vec![
+            "CONFIG_TYPE = 'GC_SERIES' and CONFIG_VALUE >= 'A''B' and NUM1 < 1.5".to_string(),
+            "valid_at(2024-01-31) and AGE_FROM > 2".to_string(),
+        ])?;
+        assert_eq!(
+            filter.oracle_conditions(config, "t")?,
+            vec![
+                "t.CDF1_ID = f.get_cdf_id('TT_CONFIG_TYPE_ID','GC_SERIES')",
+                "t.TEXT1 >= 'A''B'",
+                "t.NUM1 < 1.5",
+                "(t.VALID_FROM IS NULL OR t.VALID_FROM <= DATE '2024-01-31') \
+                 AND (t.VALID_TO IS NULL OR t.VALID_TO >= DATE '2024-01-31')",
+            ]
+        );
+        let codes = RowFilter::new(vec!["CONFIG_TYPE > 'GC'".to_string()])?;
+        assert!(codes.oracle_conditions(config, "t").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_column() {
+        let error = rows(&["COLOUR = 'RED'"]).err().unwrap().to_string();
+        assert_eq!(error, "row filter column COLOUR is not in any table");
+    }
+}
@@ -0,0 +1,267 @@
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use regex::Regex;
+
+use crate::bdt::Bdt;
+
+#[derive(Clone, Debug)]
+struct Pattern {
+    /// Pattern as given, used in reports
+    text: String,
+    regex: Regex,
+    exclude: bool,
+}
+
+impl Pattern {
+    fn parse(text: &str) -> Result<Pattern, Box<dyn Error>> {
+        let (exclude, pattern) = match text.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, text),
+        };
+        let regex = match pattern.strip_prefix("re:") {
+            Some(regex) => {
+                Regex::new(regex).map_err(|e| format!("invalid table regex '{}': {}", regex, e))?
+            }
+            None => Regex::new(&glob_regex(pattern))?,
+        };
+        Ok(Pattern {
+            text: text.to_string(),
+            regex,
+            exclude,
+        })
+    }
+}
+
+/// Anchored regex of glob, `*` and Oracle LIKE `%` match any text, `?` one character
+fn glob_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' | '%' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+/// Selection of tables by IC. Each item is an exact IC, a glob like `TT0*`, a regex
+/// prefixed with `re:`, an exclusion prefixed with `!` or `@file` listing items one per
+/// line (`#` starts a comment). Items can also be separated by commas. Tables matching
+/// any included item and no excluded one are selected, all tables if nothing is included.
+#[derive(Clone, Debug, Default)]
+pub struct TableFilter {
+    patterns: Vec<Pattern>,
+}
+
+impl TableFilter {
+    /// Parses items, reading `@file` lists
+    pub fn new(items: Vec<String>) -> Result<TableFilter, Box<dyn Error>> {
+        let mut patterns = Vec::new();
+        for item in items {
+            TableFilter::parse_item(&item, &mut patterns, &mut Vec::new())?;
+        }
+        Ok(TableFilter { patterns })
+    }
+
+    /// Parses item into patterns, `files` are table lists being read to detect cycles
+    fn parse_item(
+        item: &str,
+        patterns: &mut Vec<Pattern>,
+        files: &mut Vec<PathBuf>,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(path) = item.trim().strip_prefix('@') {
+            let file = fs::canonicalize(path)
+                .map_err(|e| format!("can not read table list {}: {}", path, e))?;
+            if files.contains(&file) {
+                return Err(format!("table list {} includes itself", path).into());
+            }
+            let content = fs::read_to_string(&file)
+                .map_err(|e| format!("can not read table list {}: {}", path, e))?;
+            files.push(file);
+            for line in content.lines() {
+                let line = line.split('#').next().unwrap_or_default();
+                TableFilter::parse_item(line, patterns, files)?;
+            }
+            files.pop();
+            return Ok(());
+        }
+        // regex may contain commas itself
+        if item.trim().trim_start_matches('!').starts_with("re:") {
+            patterns.push(Pattern::parse(item.trim())?);
+            return Ok(());
+        }
+        for text in item
+            .split(',')
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+        {
+            patterns.push(Pattern::parse(text)?);
+        }
+        Ok(())
+    }
+
+    fn includes(&self) -> impl Iterator<Item = &Pattern> {
+        self.patterns.iter().filter(|pattern| !pattern.exclude)
+    }
+
+    /// Filter has no items, all tables are selected
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Table with IC is selected
+    pub fn matches(&self, ic: &str) -> bool {
+        let mut includes = self.includes().peekable();
+        let included = includes.peek().is_none() || includes.any(|p| p.regex.is_match(ic));
+        included
+            && !self
+                .patterns
+                .iter()
+                .any(|pattern| pattern.exclude && pattern.regex.is_match(ic))
+    }
+
+    /// Selected tables
+    pub fn apply(&self, tables: Vec<Bdt>) -> Vec<Bdt> {
+        tables
+            .into_iter()
+            .filter(|bdt| self.matches(&bdt.ic))
+            .collect()
+    }
+
+    /// Items which match none of given ICs, exclusions are checked against all ICs
+    pub fn unmatched<'a>(&self, ics: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        let ics: Vec<&str> = ics.into_iter().collect();
+        self.patterns
+            .iter()
+            .filter(|pattern| !ics.iter().any(|ic| pattern.regex.is_match(ic)))
+            .map(|pattern| pattern.text.clone())
+            .collect()
+    }
+
+    /// Oracle LIKE masks covering the selection, regexes need all tables to be read
+    pub fn like_masks(&self) -> Vec<String> {
+        let mut masks: Vec<String> = Vec::new();
+        for pattern in self.includes() {
+            if pattern.text.starts_with("re:") {
+                return vec!["%".to_string()];
+            }
+            let mask = pattern.text.replace('*', "%").replace('?', "_");
+            if !masks.contains(&mask) {
+                masks.push(mask);
+            }
+        }
+        if masks.is_empty() {
+            masks.push("%".to_string());
+        }
+        masks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ICS: [&str; 5] = [
+        "TT01_POWER",
+        "TT02_DEPRECIATION",
+        "TT03_AGE",
+        "TT_CONFIG",
+        "AL01",
+    ];
+
+    fn selected(items: &[&str]) -> Vec<&'static str> {
+        let filter = TableFilter::new(items.iter().map(|i| i.to_string()).collect()).unwrap();
+        ICS.iter()
+            .copied()
+            .filter(|ic| filter.matches(ic))
+            .collect()
+    }
+
+    #[test]
+    fn select_by_list_glob_and_regex() {
+        assert_eq!(selected(&[]), ICS.to_vec());
+        assert_eq!(selected(&["TT_CONFIG", "AL01"]), vec!["TT_CONFIG", "AL01"]);
+        assert_eq!(selected(&["TT_CONFIG,AL01"]), vec!["TT_CONFIG", "AL01"]);
+        assert_eq!(
+            selected(&["TT0*"]),
+            vec!["TT01_POWER", "TT02_DEPRECIATION", "TT03_AGE"]
+        );
+        assert_eq!(selected(&["TT0?_AGE", "AL%"]), vec!["TT03_AGE", "AL01"]);
+        assert_eq!(
+            selected(&["re:^TT0[12]_"]),
+            vec!["TT01_POWER", "TT02_DEPRECIATION"]
+        );
+    }
+
+    #[test]
+    fn select_with_exclusions() {
+        assert_eq!(selected(&["TT*", "!TT_CONFIG"]).len(), 3);
+        assert_eq!(
+            selected(&["!TT*"]),
+            vec!["AL01"],
+            "only exclusions select all other tables"
+        );
+    }
+
+    #[test]
+    fn select_from_file() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!("bdt_table_list_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("tables.txt");
+        fs::write(
+            &path,
+            "# deployment\nTT01_POWER\n\nTT03_AGE # age\n!TT03*\n",
+        )?;
+        let item = format!("@{}", path.to_str().unwrap());
+        let result = selected(&[&item]);
+        fs::remove_dir_all(&dir)?;
+        assert_eq!(result, vec!["TT01_POWER"]);
+        assert!(TableFilter::new(vec!["@missing.txt".to_string()]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn reject_table_list_cycle() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!("bdt_table_cycle_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let first = dir.join("first.txt");
+        let second = dir.join("second.txt");
+        let shared = dir.join("shared.txt");
+        fs::write(&shared, "TT01_POWER\n")?;
+        fs::write(
+            &first,
+            format!("@{}\n@{}\n", shared.display(), second.display()),
+        )?;
+        fs::write(
+            &second,
+            format!("@{}\n@{}\n", shared.display(), first.display()),
+        )?;
+        let cycle = TableFilter::new(vec![format!("@{}", first.display())]);
+        let shared_twice = TableFilter::new(vec![format!("@{}", shared.display()); 2]);
+        fs::remove_dir_all(&dir)?;
+        assert!(cycle.unwrap_err().to_string().contains("includes itself"));
+        assert!(shared_twice.is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn report_unmatched() -> Result<(), Box<dyn Error>> {
+        let items = ["TT0*", "TT99", "!XX*"].map(String::from);
+        let filter = TableFilter::new(items.to_vec())?;
+        assert_eq!(filter.unmatched(ICS), vec!["TT99", "!XX*"]);
+        Ok(())
+    }
+
+    #[test]
+    fn oracle_masks() -> Result<(), Box<dyn Error>> {
+        let filter = TableFilter::new(vec!["TT0*,TT_CONFIG,!TT03*".to_string()])?;
+        assert_eq!(filter.like_masks(), vec!["TT0%", "TT_CONFIG"]);
+        let filter = TableFilter::new(vec!["re:TT".to_string()])?;
+        assert_eq!(filter.like_masks(), vec!["%"]);
+        Ok(())
+    }
+}
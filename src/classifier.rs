@@ -3,16 +3,25 @@ use serde::{Deserialize, Serialize};
 
 use crate::format::lv_date_format;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Entry of classifier, as in classifier CSV files
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ClassifierRecord {
+    /// Classifier of entry
     pub adm_codificator_id: String,
+    /// Code of parent entry, if any
     pub parent_ic: String,
+    /// Identification code of entry
     pub ic: String,
+    /// Code of entry
     pub code: String,
+    /// Date entry is valid from
     #[serde(with = "lv_date_format")]
     pub valid_from: Option<NaiveDate>,
+    /// Date entry is valid to
     #[serde(with = "lv_date_format")]
     pub valid_to: Option<NaiveDate>,
+    /// Position of entry in classifier
     pub sequence: Option<u16>,
+    /// Notes
     pub notes: String,
 }
@@ -1,37 +1,296 @@
 #![allow(dead_code)]
 
+use std::collections::HashMap;
 use std::error::Error;
-use std::fs::File;
+use std::fs;
+use std::io::Cursor;
+use std::marker::PhantomData;
+use std::path::Path;
 
-use csv::{DeserializeRecordsIntoIter, ReaderBuilder, WriterBuilder};
+use csv::{ReaderBuilder, StringRecord, StringRecordsIntoIter, WriterBuilder};
+use encoding_rs::Encoding;
 use serde::{Deserialize, Serialize};
 
 use crate::bdt::table_name::Name;
 use crate::bdt::*;
 use crate::bdt::{column_value::RowValues, table_name::NameList};
+use crate::bdt_io::{BdtSink, BdtSource};
+use crate::format::date_policy::DatePolicy;
+use crate::format::encoding::{self, OutputEncoding};
+use crate::format::number_locale::NumberLocale;
 use crate::l11n::language::Language;
 
+use self::csv_dialect::CsvDialect;
+use self::csv_error::{CsvParseErrors, ParseMode, RecordError};
+use self::csv_lint::{Lint, Linter};
 use self::csv_model::{ColumnRow, DataRow, TableRow};
 
+/// Delimiter, quote and line terminator
+pub mod csv_dialect;
+/// Rejected records and how they are handled
+pub mod csv_error;
+/// Spreadsheet artifacts reported as warnings
+pub mod csv_lint;
+/// Records of bundle files
 pub mod csv_model;
 
+/// Headers of CSV columns holding dates
+const DATE_HEADERS: [&str; 2] = ["valid_from", "valid_to"];
+
+/// Source file line of record
+fn line(record: &StringRecord) -> u64 {
+    record.position().map_or(0, |pos| pos.line() + 1)
+}
+
+/// Data CSV number columns are `num1` to `num10`
+fn is_number_header(header: &str) -> bool {
+    header
+        .strip_prefix("num")
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Options of reading CSV files
+#[derive(Clone, Debug, Default)]
+pub struct CsvOptions {
+    /// Handling of rejected records
+    pub mode: ParseMode,
+    /// Accepted date formats
+    pub dates: DatePolicy,
+    /// Decimal and thousands separators
+    pub numbers: NumberLocale,
+    /// Encoding of files without BOM, detected when not given
+    pub encoding: Option<&'static Encoding>,
+    /// Dialect, delimiter is sniffed when not given
+    pub dialect: CsvDialect,
+}
+
+/// Iterator of typed records of CSV file, dates and numbers are normalized first
 pub struct CsvReader<T> {
-    inner: DeserializeRecordsIntoIter<File, T>,
+    path: String,
+    headers: StringRecord,
+    date_fields: Vec<usize>,
+    dates: DatePolicy,
+    number_fields: Vec<usize>,
+    numbers: NumberLocale,
+    dialect: CsvDialect,
+    inner: StringRecordsIntoIter<Cursor<Vec<u8>>>,
+    errors: Vec<RecordError>,
+    rejected: Vec<StringRecord>,
+    /// Field count of records, from header or first record
+    width: Option<usize>,
+    /// Source record of last returned item
+    last: Option<StringRecord>,
+    linter: Linter,
+    record_type: PhantomData<T>,
 }
 
 impl<T> CsvReader<T> {
+    /// Reader of file with default options
     pub fn new(path: String) -> Result<Self, Box<dyn Error>>
     where
-        T: for<'a> Deserialize<'a>,
+        T: for<'a> Deserialize<'a> + Serialize + Default,
     {
-        let rdr = ReaderBuilder::new()
-            .has_headers(true)
-            .delimiter(b',')
-            .from_path(path)?;
+        CsvReader::with_options(path, &CsvOptions::default())
+    }
+
+    /// Reader of file, decoded and normalized by options
+    pub fn with_options(path: String, options: &CsvOptions) -> Result<Self, Box<dyn Error>>
+    where
+        T: for<'a> Deserialize<'a> + Serialize + Default,
+    {
+        let bytes = fs::read(&path).map_err(|e| format!("{}: {}", path, e))?;
+        let (text, _) =
+            encoding::decode(&bytes, options.encoding).map_err(|e| format!("{}: {}", path, e))?;
+        let mut dialect = options.dialect;
+        dialect.delimiter = Some(dialect.read_delimiter(&text));
+        // records of other length are rejected by the reader, keeping them for rejected file
+        let mut rdr = dialect
+            .reader(&text)
+            .flexible(true)
+            .from_reader(Cursor::new(text.into_bytes()));
+        let known = CsvReader::<T>::default_headers()?;
+        let headers = match dialect.header {
+            true => rdr.headers()?.clone(),
+            false => known.clone(),
+        };
+        let linter = Linter::new(&path, &headers, &known);
+        let width = dialect.header.then_some(headers.len());
+        let date_fields = headers
+            .iter()
+            .enumerate()
+            .filter(|(_, header)| DATE_HEADERS.contains(header))
+            .map(|(i, _)| i)
+            .collect();
+        let number_fields = headers
+            .iter()
+            .enumerate()
+            .filter(|(_, header)| is_number_header(header))
+            .map(|(i, _)| i)
+            .collect();
         Ok(CsvReader {
-            inner: rdr.into_deserialize(),
+            path,
+            headers,
+            date_fields,
+            dates: options.dates.clone(),
+            number_fields,
+            numbers: options.numbers,
+            dialect,
+            inner: rdr.into_records(),
+            errors: Vec::new(),
+            rejected: Vec::new(),
+            width,
+            last: None,
+            linter,
+            record_type: PhantomData,
         })
     }
+
+    /// Header of files without header line, in field order of the record type
+    fn default_headers() -> Result<StringRecord, Box<dyn Error>>
+    where
+        T: Serialize + Default,
+    {
+        let mut wtr = WriterBuilder::new().from_writer(Vec::new());
+        wtr.serialize(T::default())?;
+        let serialized = wtr.into_inner()?;
+        let mut rdr = ReaderBuilder::new().from_reader(serialized.as_slice());
+        Ok(rdr.headers()?.clone())
+    }
+
+    /// Reads all records, failing with every rejected record
+    pub fn read_strict(path: String) -> Result<Vec<T>, Box<dyn Error>>
+    where
+        T: for<'a> Deserialize<'a> + Serialize + Default,
+    {
+        let mut reader = CsvReader::new(path)?;
+        let records: Vec<T> = reader.by_ref().collect();
+        if !reader.errors.is_empty() {
+            return Err(Box::new(CsvParseErrors(reader.errors)));
+        }
+        Ok(records)
+    }
+
+    /// Errors of records skipped so far
+    pub fn errors(&self) -> &[RecordError] {
+        &self.errors
+    }
+
+    /// Spreadsheet artifacts found so far
+    pub fn lints(&self) -> &[Lint] {
+        self.linter.lints()
+    }
+
+    /// Writes records skipped so far to `<file>.rejected.csv` next to source file
+    pub fn write_rejected(&self) -> Result<(), Box<dyn Error>> {
+        let path = Path::new(&self.path).with_extension("rejected.csv");
+        let mut wtr = self.dialect.writer().flexible(true).from_path(path)?;
+        if self.dialect.header {
+            wtr.write_record(&self.headers)?;
+        }
+        for record in &self.rejected {
+            wtr.write_record(record)?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Custom deserializers (like date formats) report errors without field index,
+    /// then field is found by blanking fields one by one until record deserializes.
+    fn failed_field(&self, record: &StringRecord, error: &csv::Error) -> Option<usize>
+    where
+        T: for<'a> Deserialize<'a>,
+    {
+        if let csv::ErrorKind::Deserialize { err, .. } = error.kind() {
+            if let Some(field) = err.field() {
+                return Some(field as usize);
+            }
+        } else {
+            return None;
+        }
+        (0..record.len()).find(|&field| {
+            !record[field].is_empty() && {
+                let blanked: StringRecord = record
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| if i == field { "" } else { value })
+                    .collect();
+                blanked.deserialize::<T>(Some(&self.headers)).is_ok()
+            }
+        })
+    }
+
+    /// Converts dates accepted by date policy and numbers of the locale to canonical format
+    fn normalize(&self, record: StringRecord) -> Result<StringRecord, (StringRecord, RecordError)> {
+        let mut fields: Vec<String> = record.iter().map(String::from).collect();
+        let dates = self.date_fields.iter().map(|&field| {
+            let result = self.dates.normalize(&record[field]);
+            (field, result.map_err(|e| e.to_string()))
+        });
+        let numbers = self.number_fields.iter().map(|&field| {
+            let result = self.numbers.normalize(&record[field]);
+            (field, result.map_err(|e| e.to_string()))
+        });
+        for (field, result) in dates.chain(numbers) {
+            let value = &record[field];
+            if value.trim().is_empty() {
+                continue;
+            }
+            match result {
+                Ok(normalized) => fields[field] = normalized,
+                Err(message) => {
+                    let error = RecordError {
+                        file: self.path.clone(),
+                        line: line(&record),
+                        column: Some(self.headers[field].to_string()),
+                        value: Some(value.to_string()),
+                        message,
+                    };
+                    return Err((record, error));
+                }
+            }
+        }
+        let mut normalized = StringRecord::from(fields);
+        normalized.set_position(record.position().cloned());
+        Ok(normalized)
+    }
+
+    /// Rejects last returned record after checks across records, like duplicate keys
+    pub fn reject_last(&mut self, column: &str, message: String) {
+        if let Some(record) = self.last.take() {
+            let field = self.headers.iter().position(|header| header == column);
+            self.errors.push(RecordError {
+                file: self.path.clone(),
+                line: line(&record),
+                column: Some(column.to_string()),
+                value: field.and_then(|field| record.get(field).map(String::from)),
+                message,
+            });
+            self.rejected.push(record);
+        }
+    }
+
+    fn record_error(&self, record: &StringRecord, error: &csv::Error) -> RecordError
+    where
+        T: for<'a> Deserialize<'a>,
+    {
+        let field = self.failed_field(record, error);
+        let message = match error.kind() {
+            csv::ErrorKind::Deserialize { err, .. } => err.kind().to_string(),
+            _ => error.to_string(),
+        };
+        // csv positions count lines from the header, source file lines start at 1
+        let line = record
+            .position()
+            .or_else(|| error.position())
+            .map_or(0, |pos| pos.line() + 1);
+        RecordError {
+            file: self.path.clone(),
+            line,
+            column: field.and_then(|field| self.headers.get(field).map(String::from)),
+            value: field.and_then(|field| record.get(field).map(String::from)),
+            message,
+        }
+    }
 }
 
 impl<T> Iterator for CsvReader<T>
@@ -42,96 +301,237 @@ where
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            match self.inner.next() {
-                Some(v) => match v {
-                    Ok(t) => return Some(t),
-                    Err(e) => {
-                        println!("Parse error {}", e);
-                    } // on error skip
-                },
-                None => return None,
+            let record = match self.inner.next()? {
+                Ok(record) => record,
+                Err(e) => {
+                    let error = self.record_error(&StringRecord::new(), &e);
+                    self.errors.push(error);
+                    continue;
+                }
+            };
+            let width = *self.width.get_or_insert(record.len());
+            if record.len() != width {
+                self.errors.push(RecordError {
+                    file: self.path.clone(),
+                    line: line(&record),
+                    column: None,
+                    value: None,
+                    message: format!("record has {} fields, expected {}", record.len(), width),
+                });
+                self.rejected.push(record);
+                continue;
+            }
+            self.linter.check(&record, line(&record));
+            let record = match self.normalize(record) {
+                Ok(record) => record,
+                Err((record, error)) => {
+                    self.errors.push(error);
+                    self.rejected.push(record);
+                    continue;
+                }
+            };
+            match record.deserialize(Some(&self.headers)) {
+                Ok(t) => {
+                    self.last = Some(record);
+                    return Some(t);
+                }
+                Err(e) => {
+                    let error = self.record_error(&record, &e);
+                    self.errors.push(error);
+                    self.rejected.push(record);
+                }
             }
         }
     }
 }
 
+/// Tables of CSV bundle directory
 pub struct CsvAdapter {
-    path: String,
-    inner: CsvReader<TableRow>,
+    inner: std::vec::IntoIter<Bdt>,
+    lints: Vec<Lint>,
 }
 
 impl CsvAdapter {
-    pub fn new(path: String) -> CsvAdapter {
-        let inner = CsvReader::<TableRow>::new(path.clone() + "/tables.csv")
-            .expect("Error reading table csv");
-        CsvAdapter { path, inner }
+    /// Reads CSV directory in strict mode
+    pub fn new(path: String) -> Result<CsvAdapter, Box<dyn Error>> {
+        CsvAdapter::with_options(path, &CsvOptions::default())
     }
-}
 
-#[allow(clippy::never_loop)]
-impl Iterator for CsvAdapter {
-    type Item = Bdt;
+    /// Reads `tables.csv`, `columns.csv` and `data.csv` once, grouping columns and
+    /// data rows by table IC. In strict mode fails with errors of all rejected records,
+    /// in lenient mode reports them and writes rejected records to side files.
+    pub fn with_options(path: String, options: &CsvOptions) -> Result<CsvAdapter, Box<dyn Error>> {
+        let mode = options.mode;
+        let mut errors: Vec<RecordError> = Vec::new();
+        let mut lints: Vec<Lint> = Vec::new();
 
-    fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            match self.inner.next() {
-                Some(row) => {
-                    let mut bdt = Bdt {
-                        skip: row.skip,
-                        ic: row.ic,
-                        names: NameList::new(Vec::new()),
-                        valid_from: row.valid_from,
-                        valid_to: row.valid_to,
-                        columns: Vec::new(),
-                        data: Vec::new(),
-                    };
+        let mut tables =
+            CsvReader::<TableRow>::with_options(path.clone() + "/tables.csv", options)?;
+        let mut bdts: Vec<Bdt> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        while let Some(row) = tables.next() {
+            if index.contains_key(&row.ic) {
+                tables.reject_last("ic", format!("duplicate table IC {}", row.ic));
+                continue;
+            }
+            index.insert(row.ic.clone(), bdts.len());
+            bdts.push(CsvAdapter::to_bdt(row));
+        }
+        CsvAdapter::check(&tables, mode, &mut errors, &mut lints)?;
 
-                    let en_name = Name::new(
-                        Language::EN,
-                        row.name_en.clone(),
-                        row.print_name_en.clone(),
-                        row.short_print_name_en.clone(),
-                    );
-                    bdt.names.push(en_name);
-                    let lv_name = Name::new(
-                        Language::LV,
-                        row.name_lv.clone(),
-                        row.print_name_lv.clone(),
-                        row.short_print_name_lv,
-                    );
-                    bdt.names.push(lv_name);
-
-                    let columns = CsvReader::<ColumnRow>::new(self.path.clone() + "/columns.csv")
-                        .expect("Error reading column csv");
-                    for row in columns.filter(|col_row| col_row.table_type_id == bdt.ic) {
-                        let col = row.to_column();
-                        bdt.columns.push(col);
-                    }
+        let mut columns =
+            CsvReader::<ColumnRow>::with_options(path.clone() + "/columns.csv", options)?;
+        for row in columns.by_ref() {
+            if let Some(&i) = index.get(&row.table_type_id) {
+                bdts[i].columns.push(row.to_column());
+            }
+        }
+        CsvAdapter::check(&columns, mode, &mut errors, &mut lints)?;
 
-                    let data = CsvReader::<DataRow>::new(self.path.clone() + "/data.csv")
-                        .expect("Error reading data csv");
-                    for row in data.filter(|data_row| data_row.table_type == bdt.ic) {
-                        let data_row = RowValues::from_data_row(&bdt.columns, &row);
-                        bdt.data.push(data_row);
+        let mut data = CsvReader::<DataRow>::with_options(path + "/data.csv", options)?;
+        while let Some(row) = data.next() {
+            if let Some(&i) = index.get(&row.table_type) {
+                match RowValues::from_data_row(&bdts[i].columns, &row) {
+                    Ok(data_row) => bdts[i].data.push(data_row),
+                    Err(e) => {
+                        // data headers are reference codes without `_ID`, like `cdf1`
+                        let header = e.0.trim_end_matches("_ID").to_lowercase();
+                        data.reject_last(&header, e.to_string());
                     }
-
-                    return Some(bdt);
                 }
-                None => return None,
             }
         }
+        CsvAdapter::check(&data, mode, &mut errors, &mut lints)?;
+
+        if !errors.is_empty() {
+            let errors = CsvParseErrors(errors);
+            match mode {
+                ParseMode::Strict => return Err(Box::new(errors)),
+                ParseMode::Lenient => eprintln!("{}", errors),
+            }
+        }
+
+        Ok(CsvAdapter {
+            inner: bdts.into_iter(),
+            lints,
+        })
+    }
+
+    fn check<T>(
+        reader: &CsvReader<T>,
+        mode: ParseMode,
+        errors: &mut Vec<RecordError>,
+        lints: &mut Vec<Lint>,
+    ) -> Result<(), Box<dyn Error>> {
+        if mode == ParseMode::Lenient && !reader.errors().is_empty() {
+            reader.write_rejected()?;
+        }
+        errors.extend_from_slice(reader.errors());
+        lints.extend_from_slice(reader.lints());
+        Ok(())
+    }
+
+    /// Spreadsheet artifacts found in all files
+    pub fn lints(&self) -> &[Lint] {
+        &self.lints
+    }
+
+    fn to_bdt(row: TableRow) -> Bdt {
+        let mut bdt = Bdt {
+            skip: row.skip,
+            ic: row.ic,
+            names: NameList::new(Vec::new()),
+            valid_from: row.valid_from,
+            valid_to: row.valid_to,
+            columns: Vec::new(),
+            data: Vec::new(),
+        };
+
+        let en_name = Name::new(
+            Language::EN,
+            row.name_en,
+            row.print_name_en,
+            row.short_print_name_en,
+        );
+        bdt.names.push(en_name);
+        let lv_name = Name::new(
+            Language::LV,
+            row.name_lv,
+            row.print_name_lv,
+            row.short_print_name_lv,
+        );
+        bdt.names.push(lv_name);
+        bdt
+    }
+}
+
+/// CSV directory source of `convert`
+#[derive(Clone, Debug, Default)]
+pub struct CsvSource {
+    /// Options of reading CSV files
+    pub options: CsvOptions,
+}
+
+impl BdtSource for CsvSource {
+    /// Reads CSV directory, spreadsheet artifacts are reported as warnings
+    fn read(&self, location: &str) -> Result<Vec<Bdt>, Box<dyn Error>> {
+        let adapter = CsvAdapter::with_options(location.to_string(), &self.options)?;
+        for lint in adapter.lints() {
+            eprintln!("warning: {}", lint);
+        }
+        Ok(adapter.collect())
+    }
+}
+
+impl Iterator for CsvAdapter {
+    type Item = Bdt;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
     }
 }
 
-pub struct CsvWriter {}
+/// CSV bundle writer of `tables.csv`, `columns.csv` and `data.csv`
+pub struct CsvWriter {
+    numbers: NumberLocale,
+    encoding: OutputEncoding,
+    dialect: CsvDialect,
+}
+
+type DataPrepareResult = Result<(Vec<TableRow>, Vec<ColumnRow>, Vec<Vec<String>>), Box<dyn Error>>;
 
-type DataPrepareResult = Result<(Vec<TableRow>, Vec<ColumnRow>, Vec<DataRow>), Box<dyn Error>>;
+impl Default for CsvWriter {
+    fn default() -> Self {
+        CsvWriter::new()
+    }
+}
 
 impl CsvWriter {
+    /// Writer with default options
     pub fn new() -> Self {
-        CsvWriter {}
+        CsvWriter::with_numbers(NumberLocale::default())
+    }
+
+    /// Writer formatting numbers for locale
+    pub fn with_numbers(numbers: NumberLocale) -> Self {
+        CsvWriter {
+            numbers,
+            encoding: OutputEncoding::default(),
+            dialect: CsvDialect::default(),
+        }
     }
 
+    /// Writer of files in encoding
+    pub fn with_encoding(self, encoding: OutputEncoding) -> Self {
+        CsvWriter { encoding, ..self }
+    }
+
+    /// Writer of files in dialect
+    pub fn with_dialect(self, dialect: CsvDialect) -> Self {
+        CsvWriter { dialect, ..self }
+    }
+
+    /// Writes bundle files of tables to directory
     pub fn write_bdt(&self, table_list: Vec<Bdt>, path: String) -> Result<(), Box<dyn Error>> {
         let (tables, columns, datas) = self.prepeare_data(table_list)?;
 
@@ -145,7 +545,14 @@ impl CsvWriter {
 
         let mut data_path_string = path;
         data_path_string.push_str("/data.csv");
-        self.write_data(&datas, data_path_string)?;
+        let mut wtr = self.dialect.writer().from_writer(Vec::new());
+        if self.dialect.header {
+            wtr.write_record(&CsvReader::<DataRow>::default_headers()?)?;
+        }
+        for record in datas {
+            wtr.write_record(&record)?;
+        }
+        self.write_content(wtr, data_path_string)?;
 
         Ok(())
     }
@@ -153,7 +560,7 @@ impl CsvWriter {
     fn prepeare_data(&self, table_list: Vec<Bdt>) -> DataPrepareResult {
         let mut tables: Vec<TableRow> = Vec::new();
         let mut columns: Vec<ColumnRow> = Vec::new();
-        let mut datas: Vec<DataRow> = Vec::new();
+        let mut datas: Vec<Vec<String>> = Vec::new();
         for table in table_list {
             let row = csv_model::TableRow::from(&table);
             tables.push(row);
@@ -168,7 +575,7 @@ impl CsvWriter {
             for data in table.data {
                 let data_row = csv_model::DataRow::from((&data, table.ic.clone(), counter));
                 counter += 1;
-                datas.push(data_row);
+                datas.push(data_row.record(self.numbers));
             }
         }
 
@@ -179,20 +586,37 @@ impl CsvWriter {
     where
         T: Serialize,
     {
-        let mut wtr = WriterBuilder::new()
-            .has_headers(true)
-            .delimiter(b',')
-            .from_path(path)?;
+        let mut wtr = self.dialect.writer().from_writer(Vec::new());
         for record in data {
             wtr.serialize(record)?;
         }
-        wtr.flush()?;
+        self.write_content(wtr, path)
+    }
+
+    /// Encodes written records to file
+    fn write_content(&self, wtr: csv::Writer<Vec<u8>>, path: String) -> Result<(), Box<dyn Error>> {
+        let content = String::from_utf8(wtr.into_inner()?)?;
+        fs::write(&path, self.encoding.encode(&content)?)?;
         Ok(())
     }
 }
 
+impl BdtSink for CsvWriter {
+    fn write(&self, tables: Vec<Bdt>, location: &str) -> Result<(), Box<dyn Error>> {
+        self.write_bdt(tables, location.to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+
+    use crate::bdt::column_value::ColumnValueType;
+    use crate::csv_adapter::csv_dialect::LineTerminator;
+    use crate::csv_adapter::csv_lint::LintKind;
+    use std::path::PathBuf;
+
     use crate::json_adapter::JsonAdapter;
 
     use super::*;
@@ -209,6 +633,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::get_first)]
     fn read_table_csv() {
         let iter = CsvReader::<TableRow>::new(String::from("./data/TT/tables.csv"))
             .expect("Error reading csv");
@@ -219,10 +644,11 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::get_first)]
     fn read_table_with_skip_csv() {
         let iter = CsvReader::<TableRow>::new(String::from("./data/TT/tables.csv"))
             .expect("Error reading csv");
-        let v: Vec<_> = iter.filter(|row| row.skip == *"").collect();
+        let v: Vec<_> = iter.filter(|row| row.skip.is_deploy()).collect();
         assert_eq!(v.len(), 3);
         assert_eq!(
             v.get(0).unwrap().ic,
@@ -231,6 +657,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::get_first)]
     fn read_column_csv() {
         let iter = CsvReader::<ColumnRow>::new(String::from("./data/TT/columns.csv"))
             .expect("Error reading csv");
@@ -240,6 +667,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::get_first)]
     fn read_data_csv() {
         let iter = CsvReader::<DataRow>::new(String::from("./data/TT/data.csv"))
             .expect("Error reading csv");
@@ -249,6 +677,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::get_first)]
     fn read_error_data_csv() {
         let iter = CsvReader::<DataRow>::new(String::from("./data/TT/data_error.csv"))
             .expect("Error reading csv");
@@ -258,7 +687,283 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
+    fn locate_error_data_csv() {
+        let mut iter = CsvReader::<DataRow>::new(String::from("./data/TT/data_error.csv"))
+            .expect("Error reading csv");
+        let v: Vec<_> = iter.by_ref().collect();
+        assert_eq!(v.len(), 2);
+        assert_eq!(iter.errors().len(), 1);
+        let error = iter.errors().first().unwrap();
+        assert_eq!(error.file, "./data/TT/data_error.csv");
+        assert_eq!(error.line, 3);
+        assert_eq!(error.column.as_deref(), Some("valid_from"));
+        assert_eq!(error.value.as_deref(), Some("9/1/2017"));
+    }
+
+    #[test]
+    fn read_with_input_date_format() {
+        let options = CsvOptions {
+            dates: DatePolicy::new(vec!["%m/%d/%Y".to_string()], "%d.%m.%Y"),
+            ..Default::default()
+        };
+        let mut iter =
+            CsvReader::<DataRow>::with_options(String::from("./data/TT/data_error.csv"), &options)
+                .expect("Error reading csv");
+        let v: Vec<_> = iter.by_ref().collect();
+        assert_eq!(v.len(), 3);
+        assert!(iter.errors().is_empty());
+        assert_eq!(
+            v.get(1).unwrap().valid_from,
+            NaiveDate::from_ymd_opt(2017, 9, 1)
+        );
+    }
+
+    #[test]
+    fn read_strict_fails_with_errors() {
+        let result = CsvReader::<DataRow>::read_strict(String::from("./data/TT/data_error.csv"));
+        let message = result.err().unwrap().to_string();
+        assert!(message.starts_with("1 CSV record(s) rejected"));
+        assert!(message.contains("data_error.csv:3 column valid_from value '9/1/2017'"));
+    }
+
+    #[test]
+    fn write_and_read_lv_numbers() -> Result<(), Box<dyn Error>> {
+        let mut tables = JsonAdapter::read_bdt("./data/TT/TT.json")?;
+        let value = tables
+            .iter_mut()
+            .flat_map(|bdt| bdt.data.iter_mut())
+            .flat_map(|row| row.values.iter_mut())
+            .find(|value| matches!(value.value, ColumnValueType::Num(Some(_))))
+            .unwrap();
+        value.value = ColumnValueType::Num(Some(Decimal::new(12345, 1)));
+        let dir = test_dir("bdt_lv_numbers_test");
+        let path = dir.to_str().unwrap().to_string();
+        CsvWriter::with_numbers(NumberLocale::LV).write_bdt(tables, path.clone())?;
+        assert!(std::fs::read_to_string(dir.join("data.csv"))?.contains("\"1234,5\""));
+
+        let options = CsvOptions {
+            numbers: NumberLocale::LV,
+            ..Default::default()
+        };
+        let read: Vec<Bdt> = CsvAdapter::with_options(path, &options)?.collect();
+        std::fs::remove_dir_all(&dir)?;
+        let nums: Vec<Decimal> = read
+            .iter()
+            .flat_map(|bdt| bdt.data.iter())
+            .flat_map(|row| row.values.iter())
+            .filter_map(|value| match value.value {
+                ColumnValueType::Num(num) => num,
+                _ => None,
+            })
+            .collect();
+        assert!(nums.contains(&Decimal::new(12345, 1)));
+        Ok(())
+    }
+
+    #[test]
+    fn write_and_detect_windows_1257() -> Result<(), Box<dyn Error>> {
+        let tables = JsonAdapter::read_bdt("./data/TT/TT.json")?;
+        let dir = test_dir("bdt_windows_1257_test");
+        let path = dir.to_str().unwrap().to_string();
+        let encoding = OutputEncoding::new(encoding_rs::WINDOWS_1257, false)?;
+        CsvWriter::new()
+            .with_encoding(encoding)
+            .write_bdt(tables, path.clone())?;
+        assert!(String::from_utf8(std::fs::read(dir.join("tables.csv"))?).is_err());
+
+        let expected = std::fs::read_to_string("./data/TT/tables.csv")?;
+        let read: Vec<Bdt> = CsvAdapter::with_options(path, &CsvOptions::default())?.collect();
+        std::fs::remove_dir_all(&dir)?;
+        let name = read
+            .iter()
+            .flat_map(|bdt| bdt.names.names())
+            .map(|name| name.name.clone())
+            .find(|name| name.chars().any(|c| "āčēģīķļņšūž".contains(c)))
+            .unwrap();
+        assert!(expected.contains(&name));
+        Ok(())
+    }
+
+    #[test]
+    fn read_utf8_with_bom() -> Result<(), Box<dyn Error>> {
+        let dir = test_dir("bdt_bom_test");
+        let path = dir.join("tables.csv");
+        let content = std::fs::read("./data/TT/tables.csv")?;
+        std::fs::write(&path, [b"\xEF\xBB\xBF".as_slice(), &content].concat())?;
+        let v: Vec<TableRow> = CsvReader::read_strict(path.to_str().unwrap().to_string())?;
+        std::fs::remove_dir_all(&dir)?;
+        assert_eq!(v.len(), 5);
+        assert_eq!(v.first().unwrap().skip, Skip::All);
+        Ok(())
+    }
+
+    /// Directory of test, unique per process so parallel runs do not share it
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_tt(name: &str, dialect: CsvDialect) -> Result<String, Box<dyn Error>> {
+        let tables = JsonAdapter::read_bdt("./data/TT/TT.json")?;
+        let dir = test_dir(name);
+        let path = dir.to_str().unwrap().to_string();
+        CsvWriter::with_numbers(NumberLocale::LV)
+            .with_dialect(dialect)
+            .write_bdt(tables, path.clone())?;
+        Ok(path)
+    }
+
+    #[test]
+    fn write_preserves_row_skip() -> Result<(), Box<dyn Error>> {
+        let tables: Vec<Bdt> = CsvAdapter::new("./data/TT".to_string())?.collect();
+        let dir = test_dir("bdt_row_skip_test");
+        let path = dir.to_str().unwrap().to_string();
+        CsvWriter::new().write_bdt(tables, path.clone())?;
+
+        let skipped = |file: String| -> Result<usize, Box<dyn Error>> {
+            let rows: Vec<DataRow> = CsvReader::read_strict(file)?;
+            Ok(rows.iter().filter(|row| row.skip == Skip::All).count())
+        };
+        assert!(skipped("./data/TT/data.csv".to_string())? > 0);
+        assert_eq!(
+            skipped(path + "/data.csv")?,
+            skipped("./data/TT/data.csv".to_string())?
+        );
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn sniff_semicolon_dialect() -> Result<(), Box<dyn Error>> {
+        let dialect = CsvDialect {
+            delimiter: Some(b';'),
+            terminator: LineTerminator::Crlf,
+            ..Default::default()
+        };
+        let path = write_tt("bdt_semicolon_test", dialect)?;
+        let content = std::fs::read_to_string(path.clone() + "/tables.csv")?;
+        assert!(content.starts_with("skip;id;"));
+        assert!(content.contains("\r\n"));
+
+        let options = CsvOptions {
+            numbers: NumberLocale::LV,
+            ..Default::default()
+        };
+        let v: Vec<Bdt> = CsvAdapter::with_options(path.clone(), &options)?.collect();
+        std::fs::remove_dir_all(path)?;
+        assert_eq!(v.len(), 5);
+        assert_eq!(v.iter().map(|bdt| bdt.data.len()).sum::<usize>(), 27);
+        Ok(())
+    }
+
+    #[test]
+    fn read_without_header() -> Result<(), Box<dyn Error>> {
+        let dialect = CsvDialect {
+            header: false,
+            ..Default::default()
+        };
+        let path = write_tt("bdt_no_header_test", dialect)?;
+        let content = std::fs::read_to_string(path.clone() + "/tables.csv")?;
+        assert!(!content.starts_with("skip,id"));
+
+        let options = CsvOptions {
+            numbers: NumberLocale::LV,
+            dialect,
+            ..Default::default()
+        };
+        let v: Vec<Bdt> = CsvAdapter::with_options(path.clone(), &options)?.collect();
+        std::fs::remove_dir_all(path)?;
+        assert_eq!(v.len(), 5);
+        assert_eq!(v.iter().map(|bdt| bdt.columns.len()).sum::<usize>(), 23);
+        Ok(())
+    }
+
+    #[test]
+    fn lint_spreadsheet_artifacts() -> Result<(), Box<dyn Error>> {
+        let adapter = CsvAdapter::with_options(String::from("./data/TT"), &CsvOptions::default())?;
+        let lints = adapter.lints();
+        assert!(lints
+            .iter()
+            .any(|lint| lint.kind == LintKind::UnknownHeader && lint.column == "cdf16"));
+        assert!(lints.iter().any(|lint| lint.kind == LintKind::FormulaError
+            && lint.file.ends_with("tables.csv")
+            && lint.value.as_deref() == Some("#NAME?")));
+        assert!(!lints.iter().any(|lint| lint.column == "skip"));
+        Ok(())
+    }
+
+    fn error_bundle(name: &str) -> String {
+        let dir = test_dir(name);
+        std::fs::copy("./data/TT/tables.csv", dir.join("tables.csv")).unwrap();
+        std::fs::copy("./data/TT/columns.csv", dir.join("columns.csv")).unwrap();
+        std::fs::copy("./data/TT/data_error.csv", dir.join("data.csv")).unwrap();
+        dir.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn strict_adapter_fails_on_rejected_records() {
+        let path = error_bundle("bdt_strict_test");
+        let result = CsvAdapter::with_options(path.clone(), &CsvOptions::default());
+        std::fs::remove_dir_all(path).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn lenient_adapter_writes_rejected_records() -> Result<(), Box<dyn Error>> {
+        let path = error_bundle("bdt_lenient_test");
+        let options = CsvOptions {
+            mode: ParseMode::Lenient,
+            ..Default::default()
+        };
+        let v: Vec<Bdt> = CsvAdapter::with_options(path.clone(), &options)?.collect();
+        assert_eq!(v.first().unwrap().data.len(), 2);
+        let rejected = std::fs::read_to_string(path.clone() + "/data.rejected.csv")?;
+        std::fs::remove_dir_all(path)?;
+        let lines: Vec<&str> = rejected.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("skip,id,table_type,valid_from"));
+        assert!(lines[1].contains("9/1/2017"));
+        Ok(())
+    }
+
+    #[test]
+    fn duplicate_table_ic_is_rejected() -> Result<(), Box<dyn Error>> {
+        let dir = test_dir("bdt_duplicate_ic_test");
+        let tables = std::fs::read_to_string("./data/TT/tables.csv")?;
+        let duplicate = tables.lines().nth(2).unwrap();
+        let lines = tables.lines().count() + 1;
+        std::fs::write(
+            dir.join("tables.csv"),
+            format!("{}\r\n{}\r\n", tables.trim_end(), duplicate),
+        )?;
+        std::fs::copy("./data/TT/columns.csv", dir.join("columns.csv"))?;
+        std::fs::copy("./data/TT/data.csv", dir.join("data.csv"))?;
+        let path = dir.to_str().unwrap().to_string();
+
+        let message = CsvAdapter::with_options(path.clone(), &CsvOptions::default())
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(message.contains(&format!(
+            "tables.csv:{} column ic value 'TT01_POWER_FACTOR_DEFAULT_SEARCH_CONFIG': \
+             duplicate table IC TT01_POWER_FACTOR_DEFAULT_SEARCH_CONFIG",
+            lines
+        )));
+        let options = CsvOptions {
+            mode: ParseMode::Lenient,
+            ..Default::default()
+        };
+        let v: Vec<Bdt> = CsvAdapter::with_options(path, &options)?.collect();
+        assert_eq!(v.len(), 5);
+        let bdt = v.iter().find(|bdt| bdt.ic.starts_with("TT01")).unwrap();
+        assert!(!bdt.columns.is_empty());
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::get_first)]
     fn data_for_nonexistant_column() {
         let iter_d = CsvReader::<DataRow>::new(String::from("./data/TT/data_extra_data.csv"))
             .expect("Error reading csv");
@@ -274,7 +979,61 @@ mod tests {
                 test_columns.push(col);
             }
         }
-        RowValues::from_data_row(&test_columns, data.get(0).unwrap());
+        let error = RowValues::from_data_row(&test_columns, data.get(0).unwrap())
+            .err()
+            .unwrap();
+        assert_eq!(error.0, "VALID_FROM");
+    }
+
+    #[test]
+    fn adapter_rejects_data_for_undefined_column() -> Result<(), Box<dyn Error>> {
+        let dir = test_dir("bdt_undefined_column_test");
+        std::fs::copy("./data/TT/tables.csv", dir.join("tables.csv"))?;
+        std::fs::copy("./data/TT/columns.csv", dir.join("columns.csv"))?;
+        let data = std::fs::read_to_string("./data/TT/data.csv")?;
+        let extra = std::fs::read_to_string("./data/TT/data_extra_data.csv")?;
+        let extra = extra.lines().nth(1).unwrap();
+        std::fs::write(
+            dir.join("data.csv"),
+            format!("{}\r\n{}\r\n", data.trim_end(), extra),
+        )?;
+        let path = dir.to_str().unwrap().to_string();
+
+        let message = CsvAdapter::new(path.clone()).err().unwrap().to_string();
+        assert!(message.contains(&format!(
+            "data.csv:{} column valid_from value '01.09.2017': data in undefined column VALID_FROM",
+            data.trim_end().lines().count() + 1
+        )));
+        let options = CsvOptions {
+            mode: ParseMode::Lenient,
+            ..Default::default()
+        };
+        let v: Vec<Bdt> = CsvAdapter::with_options(path, &options)?.collect();
+        assert_eq!(v.iter().map(|bdt| bdt.data.len()).sum::<usize>(), 27);
+        let rejected = std::fs::read_to_string(dir.join("data.rejected.csv"))?;
+        assert!(rejected.lines().nth(1).unwrap().contains(",Y,Y,"));
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn records_of_other_length_are_rejected() -> Result<(), Box<dyn Error>> {
+        let dir = test_dir("bdt_record_length_test");
+        let path = dir.join("data.csv");
+        let data = std::fs::read_to_string("./data/TT/data.csv")?;
+        std::fs::write(&path, format!("{}\r\nskip,,TT_CONFIG\r\n", data.trim_end()))?;
+
+        let mut reader = CsvReader::<DataRow>::new(path.to_str().unwrap().to_string())?;
+        assert_eq!(reader.by_ref().count(), 27);
+        assert!(reader.errors()[0].to_string().ends_with(&format!(
+            ":{}: record has 3 fields, expected 54",
+            data.trim_end().lines().count() + 1
+        )));
+        reader.write_rejected()?;
+        let rejected = std::fs::read_to_string(dir.join("data.rejected.csv"))?;
+        assert!(rejected.ends_with("skip,,TT_CONFIG\n"));
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
     }
 
     #[test]
@@ -289,8 +1048,9 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::get_first)]
     fn read_bdt_columns_from_csv() {
-        let adapter = CsvAdapter::new(String::from("./data/TT/"));
+        let adapter = CsvAdapter::new(String::from("./data/TT/")).unwrap();
         let v: Vec<Bdt> = adapter.collect();
         assert_eq!(v.len(), 5);
         assert_eq!(v.get(0).unwrap().ic, String::from("TT_CONFIG"));
@@ -298,8 +1058,9 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::get_first)]
     fn read_bdt_data_from_csv() {
-        let adapter = CsvAdapter::new(String::from("./data/TT/"));
+        let adapter = CsvAdapter::new(String::from("./data/TT/")).unwrap();
         let v: Vec<Bdt> = adapter.collect();
         assert_eq!(v.len(), 5);
         assert_eq!(v.get(0).unwrap().ic, String::from("TT_CONFIG"));
@@ -307,8 +1068,19 @@ mod tests {
     }
 
     #[test]
+    fn read_bdt_groups_by_table() {
+        let adapter = CsvAdapter::new(String::from("./data/TT/")).unwrap();
+        let v: Vec<Bdt> = adapter.collect();
+        let columns: Vec<usize> = v.iter().map(|bdt| bdt.columns.len()).collect();
+        let data: Vec<usize> = v.iter().map(|bdt| bdt.data.len()).collect();
+        assert_eq!(columns, vec![5, 6, 4, 4, 4]);
+        assert_eq!(data, vec![15, 9, 0, 0, 3]);
+    }
+
+    #[test]
+    #[allow(clippy::get_first)]
     fn test_default_is_key() {
-        let adapter = CsvAdapter::new(String::from("./data/TT/"));
+        let adapter = CsvAdapter::new(String::from("./data/TT/")).unwrap();
         let v: Vec<Bdt> = adapter.collect();
         assert_eq!(v.len(), 5);
         assert_eq!(v.get(4).unwrap().columns.len(), 4);
@@ -0,0 +1,118 @@
+use clap::ValueEnum;
+use csv::{ReaderBuilder, Terminator, WriterBuilder};
+
+/// Delimiters tried when sniffing files whose header does not start with `skip`
+const CANDIDATES: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+/// Line terminator of written files, any terminator is accepted when reading
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum LineTerminator {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`
+    Crlf,
+}
+
+/// How fields and records of CSV files are separated and quoted
+#[derive(Clone, Copy, Debug)]
+pub struct CsvDialect {
+    /// Field delimiter, sniffed from header line when reading if not given
+    pub delimiter: Option<u8>,
+    /// Quote character
+    pub quote: u8,
+    /// Line terminator of written files
+    pub terminator: LineTerminator,
+    /// Files start with header line, otherwise fields are taken in BDT column order
+    pub header: bool,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        CsvDialect {
+            delimiter: None,
+            quote: b'"',
+            terminator: LineTerminator::Lf,
+            header: true,
+        }
+    }
+}
+
+impl CsvDialect {
+    /// Delimiter used for writing, comma if not given
+    pub fn write_delimiter(&self) -> u8 {
+        self.delimiter.unwrap_or(b',')
+    }
+
+    /// Given delimiter or the one sniffed from the first line of content
+    pub fn read_delimiter(&self, content: &str) -> u8 {
+        self.delimiter
+            .unwrap_or_else(|| sniff_delimiter(content, self.quote))
+    }
+
+    /// Reader builder of content in dialect
+    pub fn reader(&self, content: &str) -> ReaderBuilder {
+        let mut builder = ReaderBuilder::new();
+        builder
+            .has_headers(self.header)
+            .delimiter(self.read_delimiter(content))
+            .quote(self.quote);
+        builder
+    }
+
+    /// Writer builder in dialect
+    pub fn writer(&self) -> WriterBuilder {
+        let mut builder = WriterBuilder::new();
+        let terminator = match self.terminator {
+            LineTerminator::Lf => Terminator::Any(b'\n'),
+            LineTerminator::Crlf => Terminator::CRLF,
+        };
+        builder
+            .has_headers(self.header)
+            .delimiter(self.write_delimiter())
+            .quote(self.quote)
+            .terminator(terminator);
+        builder
+    }
+}
+
+/// BDT header lines start with `skip`, the character after it is the delimiter.
+/// Other files get the candidate occurring most often in the first line.
+pub fn sniff_delimiter(content: &str, quote: u8) -> u8 {
+    let line = content.lines().next().unwrap_or_default();
+    let quote = quote as char;
+    let unquoted = line
+        .strip_prefix(quote)
+        .and_then(|rest| rest.strip_prefix("skip"))
+        .and_then(|rest| rest.strip_prefix(quote))
+        .or_else(|| line.strip_prefix("skip"));
+    if let Some(&delimiter) = unquoted.and_then(|rest| rest.as_bytes().first()) {
+        if CANDIDATES.contains(&delimiter) {
+            return delimiter;
+        }
+    }
+    CANDIDATES
+        .iter()
+        .copied()
+        .max_by_key(|&candidate| line.bytes().filter(|&b| b == candidate).count())
+        .filter(|&candidate| line.as_bytes().contains(&candidate))
+        .unwrap_or(b',')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniff_from_skip_header() {
+        assert_eq!(sniff_delimiter("skip;id;table_type\n", b'"'), b';');
+        assert_eq!(sniff_delimiter("\"skip\"\tid\n", b'"'), b'\t');
+        assert_eq!(sniff_delimiter("skip,id;x\n", b'"'), b',');
+    }
+
+    #[test]
+    fn sniff_by_count() {
+        assert_eq!(sniff_delimiter("ic;code;notes,x\n", b'"'), b';');
+        assert_eq!(sniff_delimiter("ic\n", b'"'), b',');
+    }
+}
@@ -0,0 +1,58 @@
+use std::error::Error;
+use std::fmt;
+
+use clap::ValueEnum;
+
+/// How CSV records which can not be deserialized are handled
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum ParseMode {
+    /// Collect all errors and fail
+    #[default]
+    Strict,
+    /// Skip rejected records and write them to `<file>.rejected.csv`
+    Lenient,
+}
+
+/// Located CSV record error
+#[derive(Clone, Debug)]
+pub struct RecordError {
+    /// File of record
+    pub file: String,
+    /// Line of record in file
+    pub line: u64,
+    /// Header of rejected field, if known
+    pub column: Option<String>,
+    /// Rejected value, if known
+    pub value: Option<String>,
+    /// Why record is rejected
+    pub message: String,
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)?;
+        if let Some(column) = &self.column {
+            write!(f, " column {}", column)?;
+        }
+        if let Some(value) = &self.value {
+            write!(f, " value '{}'", value)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Errors of all rejected records in strict mode
+#[derive(Debug)]
+pub struct CsvParseErrors(pub Vec<RecordError>);
+
+impl fmt::Display for CsvParseErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} CSV record(s) rejected", self.0.len())?;
+        for error in &self.0 {
+            write!(f, "\n  {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for CsvParseErrors {}
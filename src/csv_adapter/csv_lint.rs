@@ -0,0 +1,149 @@
+use std::fmt;
+
+use csv::StringRecord;
+
+/// Values spreadsheets put in cells when formula fails
+pub const FORMULA_ERRORS: [&str; 7] = [
+    "#NAME?", "#REF!", "#VALUE!", "#DIV/0!", "#N/A", "#NUM!", "#NULL!",
+];
+
+/// Kind of suspicious content
+#[derive(Clone, Debug, PartialEq)]
+pub enum LintKind {
+    /// Value is one of [`FORMULA_ERRORS`]
+    FormulaError,
+    /// Header which is not a field of the record, its values are ignored
+    UnknownHeader,
+    /// Value under empty header, ignored as well
+    UnnamedValue,
+}
+
+/// Suspicious CSV content which does not reject the record
+#[derive(Clone, Debug)]
+pub struct Lint {
+    /// File of content
+    pub file: String,
+    /// Line of record, none for header
+    pub line: Option<u64>,
+    /// Header of column, or `#<n>` when empty
+    pub column: String,
+    /// Suspicious value, none for header
+    pub value: Option<String>,
+    /// What is suspicious
+    pub kind: LintKind,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        write!(f, " column {}", self.column)?;
+        if let Some(value) = &self.value {
+            write!(f, " value '{}'", value)?;
+        }
+        let message = match self.kind {
+            LintKind::FormulaError => "spreadsheet formula error",
+            LintKind::UnknownHeader => "unknown header, column is ignored",
+            LintKind::UnnamedValue => "value under unnamed header is ignored",
+        };
+        write!(f, ": {}", message)
+    }
+}
+
+/// Collects lints of file records
+pub struct Linter {
+    file: String,
+    columns: Vec<String>,
+    unnamed: Vec<bool>,
+    lints: Vec<Lint>,
+}
+
+impl Linter {
+    /// Reports headers of file which are not among known headers of the record type
+    pub fn new(file: &str, headers: &StringRecord, known: &StringRecord) -> Linter {
+        let columns: Vec<String> = headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| match header.trim() {
+                "" => format!("#{}", i + 1),
+                header => header.to_string(),
+            })
+            .collect();
+        let unnamed = headers.iter().map(|h| h.trim().is_empty()).collect();
+        let lints = headers
+            .iter()
+            .filter(|header| {
+                !header.trim().is_empty() && !known.iter().any(|known| known == *header)
+            })
+            .map(|header| Lint {
+                file: file.to_string(),
+                line: None,
+                column: header.to_string(),
+                value: None,
+                kind: LintKind::UnknownHeader,
+            })
+            .collect();
+        Linter {
+            file: file.to_string(),
+            columns,
+            unnamed,
+            lints,
+        }
+    }
+
+    /// Reports formula errors and values under empty headers of record at line
+    pub fn check(&mut self, record: &StringRecord, line: u64) {
+        for (i, value) in record.iter().enumerate() {
+            let kind = if FORMULA_ERRORS.contains(&value.trim()) {
+                LintKind::FormulaError
+            } else if !value.trim().is_empty() && self.unnamed.get(i).copied().unwrap_or(true) {
+                LintKind::UnnamedValue
+            } else {
+                continue;
+            };
+            self.lints.push(Lint {
+                file: self.file.clone(),
+                line: Some(line),
+                column: self
+                    .columns
+                    .get(i)
+                    .cloned()
+                    .unwrap_or(format!("#{}", i + 1)),
+                value: Some(value.to_string()),
+                kind,
+            });
+        }
+    }
+
+    /// Lints found so far
+    pub fn lints(&self) -> &[Lint] {
+        &self.lints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lint_headers_and_values() {
+        let headers = StringRecord::from(vec!["skip", "ic", "cdf16", ""]);
+        let known = StringRecord::from(vec!["skip", "ic"]);
+        let mut linter = Linter::new("data.csv", &headers, &known);
+        linter.check(&StringRecord::from(vec!["", "#REF!", "x", "#NAME?"]), 2);
+        linter.check(&StringRecord::from(vec!["", "TT", "", "note"]), 3);
+
+        let lints: Vec<String> = linter.lints().iter().map(|l| l.to_string()).collect();
+        assert_eq!(
+            lints,
+            vec![
+                "data.csv column cdf16: unknown header, column is ignored",
+                "data.csv:2 column ic value '#REF!': spreadsheet formula error",
+                "data.csv:2 column #4 value '#NAME?': spreadsheet formula error",
+                "data.csv:3 column #4 value 'note': value under unnamed header is ignored",
+            ]
+        );
+    }
+}
@@ -1,14 +1,18 @@
 use chrono::NaiveDate;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 use crate::bdt::column_type::ColumnType;
 use crate::bdt::*;
 use crate::format::lv_date_format;
+use crate::format::number_locale::NumberLocale;
 use crate::l11n::language::Language;
 
-#[derive(Debug, Deserialize, Serialize)]
+/// Record of `tables.csv`, fields are named by headers
+#[allow(missing_docs)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct TableRow {
-    pub skip: String,
+    pub skip: Skip,
     pub id: Option<u64>,
     pub adm_codificator_ic: String,
     pub parent_ic: String,
@@ -32,7 +36,7 @@ pub struct TableRow {
 impl From<&Bdt> for TableRow {
     fn from(bdtrec: &Bdt) -> Self {
         TableRow {
-            skip: bdtrec.skip.clone(),
+            skip: bdtrec.skip,
             id: None,
             adm_codificator_ic: "AGC_CUSTOM_TABLE_TYPE".to_string(),
             parent_ic: "".to_string(),
@@ -77,9 +81,11 @@ impl From<&Bdt> for TableRow {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// Record of `columns.csv`, fields are named by headers
+#[allow(missing_docs)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct ColumnRow {
-    pub skip: String,
+    pub skip: Skip,
     pub id: Option<u64>,
     pub table_type_id: String,
     pub title: String,
@@ -93,9 +99,10 @@ pub struct ColumnRow {
 }
 
 impl ColumnRow {
+    /// Column of record
     pub fn to_column(&self) -> Column {
         Column {
-            skip: self.skip.clone(),
+            skip: self.skip,
             id: self.id,
             name: self.col_name.clone(),
             title: self.title.clone(),
@@ -123,7 +130,7 @@ impl From<(&Column, String, u16)> for ColumnRow {
             _ => ("".to_string(), "".to_string()),
         };
         ColumnRow {
-            skip: value.0.skip.clone(),
+            skip: value.0.skip,
             id: value.0.id,
             table_type_id: value.1.clone(),
             title: value.0.title.clone(),
@@ -138,9 +145,11 @@ impl From<(&Column, String, u16)> for ColumnRow {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// Record of `data.csv`, fields are named by headers
+#[allow(missing_docs)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct DataRow {
-    pub skip: String,
+    pub skip: Skip,
     pub id: Option<u64>,
     pub table_type: String,
     #[serde(with = "lv_date_format")]
@@ -162,16 +171,26 @@ pub struct DataRow {
     pub cdf13: String,
     pub cdf14: String,
     pub cdf15: String,
-    pub num1: Option<f64>,
-    pub num2: Option<f64>,
-    pub num3: Option<f64>,
-    pub num4: Option<f64>,
-    pub num5: Option<f64>,
-    pub num6: Option<f64>,
-    pub num7: Option<f64>,
-    pub num8: Option<f64>,
-    pub num9: Option<f64>,
-    pub num10: Option<f64>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub num1: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub num2: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub num3: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub num4: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub num5: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub num6: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub num7: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub num8: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub num9: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub num10: Option<Decimal>,
     pub text1: String,
     pub text2: String,
     pub text3: String,
@@ -179,10 +198,58 @@ pub struct DataRow {
     pub text5: String,
 }
 
+impl DataRow {
+    /// Fields in header order, numbers formatted for the locale
+    pub fn record(&self, numbers: NumberLocale) -> Vec<String> {
+        let date = |date: Option<NaiveDate>| {
+            date.map(|date| date.format(lv_date_format::FORMAT).to_string())
+                .unwrap_or_default()
+        };
+        let number = |num: Option<Decimal>| num.map(|num| numbers.format(num)).unwrap_or_default();
+        vec![
+            self.skip.to_string(),
+            self.id.map(|id| id.to_string()).unwrap_or_default(),
+            self.table_type.clone(),
+            date(self.valid_from),
+            date(self.valid_to),
+            self.cdf1.clone(),
+            self.cdf2.clone(),
+            self.cdf3.clone(),
+            self.cdf4.clone(),
+            self.cdf5.clone(),
+            self.cdf6.clone(),
+            self.cdf7.clone(),
+            self.cdf8.clone(),
+            self.cdf9.clone(),
+            self.cdf10.clone(),
+            self.cdf11.clone(),
+            self.cdf12.clone(),
+            self.cdf13.clone(),
+            self.cdf14.clone(),
+            self.cdf15.clone(),
+            number(self.num1),
+            number(self.num2),
+            number(self.num3),
+            number(self.num4),
+            number(self.num5),
+            number(self.num6),
+            number(self.num7),
+            number(self.num8),
+            number(self.num9),
+            number(self.num10),
+            self.text1.clone(),
+            self.text2.clone(),
+            self.text3.clone(),
+            self.text4.clone(),
+            self.text5.clone(),
+        ]
+    }
+}
+
 impl From<(&RowValues, String, u16)> for DataRow {
     fn from(value: (&RowValues, String, u16)) -> Self {
         DataRow {
-            skip: "".to_string(),
+            skip: value.0.skip,
             id: None,
             table_type: value.1.clone(),
             valid_from: value.0.get_by_ref_as_date("VALID_FROM"),
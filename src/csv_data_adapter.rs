@@ -1,23 +1,42 @@
 use std::error::Error;
-
-use csv::WriterBuilder;
+use std::fs;
 
 use crate::bdt::{column_value::ColumnValueType, Bdt};
+use crate::csv_adapter::csv_dialect::CsvDialect;
+use crate::format::date_policy::DatePolicy;
+use crate::format::encoding::OutputEncoding;
+use crate::format::number_locale::NumberLocale;
 
-const FORMAT: &str = "%Y-%m-%d";
+/// Default date format
+pub const FORMAT: &str = "%Y-%m-%d";
 
-pub fn write_csv_data(path: &String, bdt: &Bdt) -> Result<(), Box<dyn Error>> {
-    let data = make_data_rows(bdt);
-    let mut wtr = WriterBuilder::new().from_path(path)?;
-    for row in data {
+/// Writes data rows of table as CSV with column names as headers
+pub fn write_csv_data(
+    path: &String,
+    bdt: Bdt,
+    dates: &DatePolicy,
+    numbers: NumberLocale,
+    encoding: &OutputEncoding,
+    dialect: &CsvDialect,
+) -> Result<(), Box<dyn Error>> {
+    let ic = bdt.ic.clone();
+    let bdt = bdt
+        .deployable()
+        .ok_or(format!("Table {} is marked skip", ic))?;
+    let data = make_data_rows(&bdt, dates, numbers);
+    let mut wtr = dialect.writer().from_writer(Vec::new());
+    let skip_header = if dialect.header { 0 } else { 1 };
+    for row in data.into_iter().skip(skip_header) {
         wtr.write_record(row)?;
     }
+    let content = String::from_utf8(wtr.into_inner()?)?;
+    fs::write(path, encoding.encode(&content)?)?;
     Ok(())
 }
 
-fn make_data_rows(bdt: &Bdt) -> Vec<Vec<String>> {
+fn make_data_rows(bdt: &Bdt, dates: &DatePolicy, numbers: NumberLocale) -> Vec<Vec<String>> {
     let csv_hader = make_header(bdt);
-    make_rows(bdt, csv_hader)
+    make_rows(bdt, csv_hader, dates, numbers)
 }
 
 fn make_header(bdt: &Bdt) -> Vec<String> {
@@ -28,7 +47,12 @@ fn make_header(bdt: &Bdt) -> Vec<String> {
     result
 }
 
-fn make_rows(bdt: &Bdt, header: Vec<String>) -> Vec<Vec<String>> {
+fn make_rows(
+    bdt: &Bdt,
+    header: Vec<String>,
+    dates: &DatePolicy,
+    numbers: NumberLocale,
+) -> Vec<Vec<String>> {
     let mut result: Vec<Vec<String>> = vec![];
     result.push(header);
     for row in &bdt.data {
@@ -42,12 +66,12 @@ fn make_rows(bdt: &Bdt, header: Vec<String>) -> Vec<Vec<String>> {
                         ColumnValueType::Text(str_val) => str_value = str_val.clone(),
                         ColumnValueType::Date(date_opt_val) => {
                             if let Some(date_val) = date_opt_val {
-                                str_value = format!("{}", date_val.clone().format(FORMAT));
+                                str_value = dates.format(*date_val);
                             }
                         }
                         ColumnValueType::Num(num_opt_val) => {
                             if let Some(num_val) = num_opt_val {
-                                str_value = num_val.clone().to_string()
+                                str_value = numbers.format(*num_val)
                             }
                         }
                     }
@@ -65,19 +89,20 @@ mod tests {
     use std::vec;
 
     use chrono::NaiveDate;
+    use rust_decimal::Decimal;
 
     use crate::bdt::{
         column_type::ColumnType,
         column_value::{ColumnValue, ColumnValueType},
         table_name::NameList,
-        Column, RowValues,
+        Column, RowValues, Skip,
     };
 
     use super::*;
 
     fn create_test_data() -> Bdt {
         let col1 = Column {
-            skip: "".to_string(),
+            skip: Skip::default(),
             id: Some(1),
             name: "VALID_FROM".to_string(),
             title: "".to_string(),
@@ -88,7 +113,7 @@ mod tests {
             options: "".to_string(),
         };
         let col2 = Column {
-            skip: "".to_string(),
+            skip: Skip::default(),
             id: Some(2),
             name: "VALID_TO".to_string(),
             title: "".to_string(),
@@ -99,7 +124,7 @@ mod tests {
             options: "".to_string(),
         };
         let col3 = Column {
-            skip: "".to_string(),
+            skip: Skip::default(),
             id: Some(3),
             name: "CONFIG_TYPE".to_string(),
             title: "".to_string(),
@@ -113,7 +138,7 @@ mod tests {
             options: "".to_string(),
         };
         let col4 = Column {
-            skip: "".to_string(),
+            skip: Skip::default(),
             id: Some(4),
             name: "ADDITIONAL_COL".to_string(),
             title: "".to_string(),
@@ -127,7 +152,7 @@ mod tests {
             options: "".to_string(),
         };
         let col5 = Column {
-            skip: "".to_string(),
+            skip: Skip::default(),
             id: Some(5),
             name: "CONFIG_VALUE".to_string(),
             title: "".to_string(),
@@ -138,7 +163,7 @@ mod tests {
             options: "".to_string(),
         };
         let col6 = Column {
-            skip: "".to_string(),
+            skip: Skip::default(),
             id: Some(6),
             name: "CONFIG_NUM_VALUE".to_string(),
             title: "".to_string(),
@@ -171,13 +196,13 @@ mod tests {
             ref_code: "TEXT1".to_string(),
         });
         row1.push(ColumnValue {
-            value: ColumnValueType::Num(Some(10.0)),
+            value: ColumnValueType::Num(Some(Decimal::new(10, 0))),
             name: "CONFIG_NUM_VALUE".to_string(),
             ref_code: "NUM1".to_string(),
         });
 
         Bdt {
-            skip: "".to_string(),
+            skip: Skip::default(),
             ic: "".to_string(),
             names: NameList::new(vec![]),
             valid_from: None,
@@ -188,6 +213,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::get_first)]
     fn make_header_test() {
         let bdt = create_test_data();
         let csv_hader = make_header(&bdt);
@@ -196,13 +222,42 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::get_first)]
     fn make_row_test() {
         let bdt = create_test_data();
-        let csv_row = make_rows(&bdt, vec![]);
+        let csv_row = make_rows(
+            &bdt,
+            vec![],
+            &DatePolicy::new(vec![], FORMAT),
+            NumberLocale::EN,
+        );
+        // `csv data` writes dates in FORMAT, "01.12.1997" never matched it
         assert_eq!(
             csv_row.get(1).unwrap().get(0).unwrap(),
-            &"01.12.1997".to_string()
+            &"1997-12-01".to_string()
         );
         assert_eq!(csv_row.get(1).unwrap().len(), 6);
     }
+
+    #[test]
+    fn make_row_date_format_test() {
+        let bdt = create_test_data();
+        let csv_row = make_rows(&bdt, vec![], &DatePolicy::default(), NumberLocale::EN);
+        assert_eq!(
+            csv_row.get(1).unwrap().first().unwrap(),
+            &"01.12.1997".to_string()
+        );
+    }
+
+    #[test]
+    fn make_row_number_locale_test() {
+        let mut bdt = create_test_data();
+        bdt.data[0].push(ColumnValue {
+            value: ColumnValueType::Num(Some(Decimal::new(12345, 1))),
+            name: "CONFIG_NUM_VALUE".to_string(),
+            ref_code: "CONFIG_NUM_VALUE".to_string(),
+        });
+        let csv_row = make_rows(&bdt, vec![], &DatePolicy::default(), NumberLocale::LV);
+        assert_eq!(csv_row.get(1).unwrap().last().unwrap(), "1234,5");
+    }
 }
@@ -1 +1,8 @@
+/// Date formats accepted on input and written on output
+pub mod date_policy;
+/// File encodings and BOM
+pub mod encoding;
+/// Serde of BDT dates, as `dd.mm.yyyy`
 pub mod lv_date_format;
+/// Number separators
+pub mod number_locale;
@@ -0,0 +1,156 @@
+use std::error::Error;
+use std::fmt;
+
+use chrono::NaiveDate;
+
+use crate::format::lv_date_format;
+
+/// Accepted input formats, tried in order
+pub const INPUT_FORMATS: [&str; 4] = ["%d.%m.%Y", "%Y-%m-%d", "%d/%m/%Y", "%m/%d/%Y"];
+
+/// Date value not matching input formats
+#[derive(Debug, PartialEq)]
+pub enum DateError {
+    /// Value is not a date in any format
+    Invalid(String),
+    /// Value is matched by several formats giving different dates, like `9/1/2017`
+    Ambiguous(String, Vec<NaiveDate>),
+}
+
+impl fmt::Display for DateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DateError::Invalid(value) => write!(f, "invalid date '{}'", value),
+            DateError::Ambiguous(value, dates) => {
+                let candidates: Vec<String> = dates
+                    .iter()
+                    .map(|date| date.format(lv_date_format::FORMAT).to_string())
+                    .collect();
+                write!(
+                    f,
+                    "ambiguous date '{}' could be {}, provide input date format",
+                    value,
+                    candidates.join(" or ")
+                )
+            }
+        }
+    }
+}
+
+impl Error for DateError {}
+
+/// Ordered list of accepted input formats and output format of an adapter
+#[derive(Clone, Debug)]
+pub struct DatePolicy {
+    /// Formats tried in order when reading
+    pub input_formats: Vec<String>,
+    /// Format of written dates
+    pub output_format: String,
+}
+
+impl Default for DatePolicy {
+    fn default() -> Self {
+        DatePolicy {
+            input_formats: INPUT_FORMATS.iter().map(|f| f.to_string()).collect(),
+            output_format: lv_date_format::FORMAT.to_string(),
+        }
+    }
+}
+
+impl DatePolicy {
+    /// Policy with input formats, default ones when empty
+    pub fn new(input_formats: Vec<String>, output_format: &str) -> Self {
+        let input_formats = if input_formats.is_empty() {
+            DatePolicy::default().input_formats
+        } else {
+            input_formats
+        };
+        DatePolicy {
+            input_formats,
+            output_format: output_format.to_string(),
+        }
+    }
+
+    /// Parses value with every input format, fails when formats disagree
+    pub fn parse(&self, value: &str) -> Result<NaiveDate, DateError> {
+        let value = value.trim();
+        let mut dates: Vec<NaiveDate> = Vec::new();
+        for format in &self.input_formats {
+            if let Ok(date) = NaiveDate::parse_from_str(value, format) {
+                if !dates.contains(&date) {
+                    dates.push(date);
+                }
+            }
+        }
+        match dates.len() {
+            0 => Err(DateError::Invalid(value.to_string())),
+            1 => Ok(dates[0]),
+            _ => Err(DateError::Ambiguous(value.to_string(), dates)),
+        }
+    }
+
+    /// Date in output format
+    pub fn format(&self, date: NaiveDate) -> String {
+        date.format(&self.output_format).to_string()
+    }
+
+    /// Converts value to canonical BDT date format used in JSON and CSV bundles
+    pub fn normalize(&self, value: &str) -> Result<String, DateError> {
+        self.parse(value)
+            .map(|date| date.format(lv_date_format::FORMAT).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_known_formats() {
+        let policy = DatePolicy::default();
+        let date = NaiveDate::from_ymd_opt(2017, 9, 25).unwrap();
+        assert_eq!(policy.parse("25.09.2017"), Ok(date));
+        assert_eq!(policy.parse("2017-09-25"), Ok(date));
+        assert_eq!(policy.parse("9/25/2017"), Ok(date));
+        assert_eq!(policy.parse("25/9/2017"), Ok(date));
+    }
+
+    #[test]
+    fn detect_ambiguous_date() {
+        let policy = DatePolicy::default();
+        let result = policy.parse("9/1/2017");
+        assert_eq!(
+            result,
+            Err(DateError::Ambiguous(
+                "9/1/2017".to_string(),
+                vec![
+                    NaiveDate::from_ymd_opt(2017, 1, 9).unwrap(),
+                    NaiveDate::from_ymd_opt(2017, 9, 1).unwrap()
+                ]
+            ))
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "ambiguous date '9/1/2017' could be 09.01.2017 or 01.09.2017, provide input date format"
+        );
+    }
+
+    #[test]
+    fn explicit_format_resolves_ambiguity() {
+        let policy = DatePolicy::new(vec!["%m/%d/%Y".to_string()], "%Y-%m-%d");
+        assert_eq!(policy.normalize("9/1/2017"), Ok("01.09.2017".to_string()));
+        assert_eq!(
+            policy.format(NaiveDate::from_ymd_opt(2017, 9, 1).unwrap()),
+            "2017-09-01"
+        );
+    }
+
+    #[test]
+    fn reject_invalid_date() {
+        let policy = DatePolicy::default();
+        assert_eq!(
+            policy.parse("31.02.2017"),
+            Err(DateError::Invalid("31.02.2017".to_string()))
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use std::error::Error;
+use std::fmt;
+
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1257};
+
+/// Encoding of files without BOM which are not valid UTF-8, as saved by Baltic Excel
+pub const FALLBACK: &Encoding = WINDOWS_1257;
+
+const UTF_8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Content not decodable or encodable
+#[derive(Debug, PartialEq)]
+pub enum EncodingError {
+    /// Content is not valid in named encoding
+    Decode(&'static str),
+    /// Content has characters named encoding can not represent
+    Encode(&'static str),
+    /// Encoding can not be written
+    Unsupported(String),
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodingError::Decode(name) => write!(f, "content is not valid {}", name),
+            EncodingError::Encode(name) => {
+                write!(f, "content has characters not representable in {}", name)
+            }
+            EncodingError::Unsupported(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for EncodingError {}
+
+/// Parses encoding label like `utf-8` or `windows-1257`, used as clap value parser
+pub fn parse_label(label: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label_no_replacement(label.as_bytes())
+        .ok_or(format!("unknown encoding '{}'", label))
+}
+
+/// Decodes file content. BOM takes precedence over given encoding, without both
+/// valid UTF-8 is taken as UTF-8 and anything else as [`FALLBACK`].
+pub fn decode(
+    bytes: &[u8],
+    encoding: Option<&'static Encoding>,
+) -> Result<(String, &'static Encoding), EncodingError> {
+    let (encoding, content) = match Encoding::for_bom(bytes) {
+        Some((encoding, bom_length)) => (encoding, &bytes[bom_length..]),
+        None => match encoding {
+            Some(encoding) => (encoding, bytes),
+            None if std::str::from_utf8(bytes).is_ok() => (UTF_8, bytes),
+            None => (FALLBACK, bytes),
+        },
+    };
+    encoding
+        .decode_without_bom_handling_and_without_replacement(content)
+        .map(|text| (text.into_owned(), encoding))
+        .ok_or(EncodingError::Decode(encoding.name()))
+}
+
+/// Encoding of written CSV files
+#[derive(Clone, Copy, Debug)]
+pub struct OutputEncoding {
+    /// Encoding of content
+    pub encoding: &'static Encoding,
+    /// Starts UTF-8 files with BOM, so Excel recognizes the encoding
+    pub bom: bool,
+}
+
+impl Default for OutputEncoding {
+    fn default() -> Self {
+        OutputEncoding {
+            encoding: UTF_8,
+            bom: false,
+        }
+    }
+}
+
+impl OutputEncoding {
+    /// Output encoding, fails for encodings without output and BOM of other than UTF-8
+    pub fn new(encoding: &'static Encoding, bom: bool) -> Result<Self, EncodingError> {
+        if encoding.output_encoding() != encoding {
+            return Err(EncodingError::Unsupported(format!(
+                "{} can not be used for output",
+                encoding.name()
+            )));
+        }
+        if bom && encoding != UTF_8 {
+            return Err(EncodingError::Unsupported(format!(
+                "BOM can only be written for UTF-8, not {}",
+                encoding.name()
+            )));
+        }
+        Ok(OutputEncoding { encoding, bom })
+    }
+
+    /// Encoded text, preceded by BOM if set
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>, EncodingError> {
+        let (content, _, unmappable) = self.encoding.encode(text);
+        if unmappable {
+            return Err(EncodingError::Encode(self.encoding.name()));
+        }
+        let mut bytes = Vec::with_capacity(content.len() + UTF_8_BOM.len());
+        if self.bom {
+            bytes.extend_from_slice(UTF_8_BOM);
+        }
+        bytes.extend_from_slice(&content);
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::UTF_16LE;
+
+    use super::*;
+
+    #[test]
+    fn decode_utf8_with_bom() {
+        let (text, encoding) = decode(b"\xEF\xBB\xBFskip,ic", Some(WINDOWS_1257)).unwrap();
+        assert_eq!(text, "skip,ic");
+        assert_eq!(encoding, UTF_8);
+    }
+
+    #[test]
+    fn detect_windows_1257() {
+        let (text, encoding) = decode(b"\xE2\xE8\xE7\xEC", None).unwrap();
+        assert_eq!(text, "āčēģ");
+        assert_eq!(encoding, WINDOWS_1257);
+        assert_eq!(decode("āčēģ".as_bytes(), None).unwrap().1, UTF_8);
+    }
+
+    #[test]
+    fn reject_invalid_explicit_encoding() {
+        assert_eq!(
+            decode(b"\xE2\xE8", Some(UTF_8)),
+            Err(EncodingError::Decode("UTF-8"))
+        );
+    }
+
+    #[test]
+    fn encode_with_bom() {
+        let output = OutputEncoding::new(UTF_8, true).unwrap();
+        assert_eq!(output.encode("ā").unwrap(), b"\xEF\xBB\xBF\xC4\x81");
+        assert!(OutputEncoding::new(WINDOWS_1257, true).is_err());
+        assert!(OutputEncoding::new(UTF_16LE, false).is_err());
+    }
+
+    #[test]
+    fn encode_windows_1257() {
+        let output = OutputEncoding::new(WINDOWS_1257, false).unwrap();
+        assert_eq!(output.encode("āčēģ").unwrap(), b"\xE2\xE8\xE7\xEC");
+        assert_eq!(
+            output.encode("ж"),
+            Err(EncodingError::Encode("windows-1257"))
+        );
+    }
+}
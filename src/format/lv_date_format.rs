@@ -2,8 +2,10 @@ use chrono::NaiveDate;
 use serde::{self, de, Deserializer, Serializer};
 use std::fmt;
 
+/// BDT date format
 pub const FORMAT: &str = "%d.%m.%Y";
 
+/// Serializes date in BDT format, none as null
 pub fn serialize<S>(some_date: &Option<NaiveDate>, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -17,6 +19,7 @@ where
     }
 }
 
+/// Deserializes date in BDT format, null as none
 pub fn deserialize<'de, D>(d: D) -> Result<Option<NaiveDate>, D::Error>
 where
     D: Deserializer<'de>,
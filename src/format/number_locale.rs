@@ -0,0 +1,153 @@
+use std::error::Error;
+use std::fmt;
+
+use clap::ValueEnum;
+use rust_decimal::Decimal;
+
+/// Decimal and thousands separators of numbers in CSV files and generated scripts
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum NumberLocale {
+    /// `1,234.5`
+    #[default]
+    EN,
+    /// `1 234,5`, as exported by Latvian Excel
+    LV,
+}
+
+/// Value which is not a number in locale
+#[derive(Debug, PartialEq)]
+pub struct NumberError(pub String);
+
+impl fmt::Display for NumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid number '{}'", self.0)
+    }
+}
+
+impl Error for NumberError {}
+
+impl NumberLocale {
+    /// Decimal separator of locale
+    pub fn decimal_separator(&self) -> char {
+        match self {
+            NumberLocale::EN => '.',
+            NumberLocale::LV => ',',
+        }
+    }
+
+    /// Accepted thousands separators, LV Excel uses no-break spaces
+    fn group_separators(&self) -> &'static [char] {
+        match self {
+            NumberLocale::EN => &[','],
+            NumberLocale::LV => &[' ', '\u{a0}', '\u{202f}'],
+        }
+    }
+
+    /// Oracle NLS_NUMERIC_CHARACTERS, decimal character followed by group separator
+    pub fn nls_numeric_characters(&self) -> &'static str {
+        match self {
+            NumberLocale::EN => ". ",
+            NumberLocale::LV => ", ",
+        }
+    }
+
+    /// Parses number, thousands separators are accepted only between groups of three digits.
+    /// Leading `+` and exponent like `1e5` are accepted too, as in Excel exports.
+    pub fn parse(&self, value: &str) -> Result<Decimal, NumberError> {
+        let trimmed = value.trim();
+        let invalid = || NumberError(trimmed.to_string());
+        if let Some((mantissa, exponent)) = trimmed.split_once(['e', 'E']) {
+            let mantissa = self.parse(mantissa).map_err(|_| invalid())?;
+            let exponent: i32 = exponent.parse().map_err(|_| invalid())?;
+            return Decimal::from_scientific(&format!("{}e{}", mantissa, exponent))
+                .map(|num| num.normalize())
+                .map_err(|_| invalid());
+        }
+        let (integer, fraction) = match trimmed.split_once(self.decimal_separator()) {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (trimmed, None),
+        };
+        let (sign, integer) = match integer.strip_prefix('-') {
+            Some(integer) => ("-", integer),
+            None => ("", integer.strip_prefix('+').unwrap_or(integer)),
+        };
+        let groups: Vec<&str> = integer.split(self.group_separators()).collect();
+        let grouped = groups
+            .iter()
+            .skip(1)
+            .all(|group| group.len() == 3 && !groups[0].is_empty());
+        if !grouped {
+            return Err(invalid());
+        }
+        let digits = groups.concat();
+        let all_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+        let empty = digits.is_empty() && fraction.is_none_or(str::is_empty);
+        if empty || !all_digits(&digits) || !fraction.is_none_or(all_digits) {
+            return Err(invalid());
+        }
+        let canonical = match fraction {
+            Some(fraction) => format!("{}{}.{}", sign, digits, fraction),
+            None => format!("{}{}", sign, digits),
+        };
+        canonical.parse::<Decimal>().map_err(|_| invalid())
+    }
+
+    /// Number with decimal separator of locale, without thousands separators
+    pub fn format(&self, num: Decimal) -> String {
+        num.to_string()
+            .replace('.', &self.decimal_separator().to_string())
+    }
+
+    /// Converts value to canonical number format used in JSON and CSV bundles
+    pub fn normalize(&self, value: &str) -> Result<String, NumberError> {
+        self.parse(value).map(|num| num.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_lv_numbers() {
+        let locale = NumberLocale::LV;
+        assert_eq!(locale.parse("1,5"), Ok(dec("1.5")));
+        assert_eq!(locale.parse("1 234,5"), Ok(dec("1234.5")));
+        assert_eq!(locale.parse("1\u{a0}234\u{a0}567"), Ok(dec("1234567")));
+        assert_eq!(locale.parse("-0,25"), Ok(dec("-0.25")));
+        assert_eq!(locale.parse("1.5"), Err(NumberError("1.5".to_string())));
+    }
+
+    #[test]
+    fn parse_en_numbers() {
+        let locale = NumberLocale::EN;
+        assert_eq!(locale.parse("1.5"), Ok(dec("1.5")));
+        assert_eq!(locale.parse("1,234.5"), Ok(dec("1234.5")));
+        assert_eq!(locale.parse("99"), Ok(dec("99")));
+        assert_eq!(locale.parse("1,5"), Err(NumberError("1,5".to_string())));
+        assert_eq!(locale.parse("-"), Err(NumberError("-".to_string())));
+    }
+
+    #[test]
+    fn parse_sign_and_exponent() {
+        let locale = NumberLocale::EN;
+        assert_eq!(locale.parse("+1"), Ok(dec("1")));
+        assert_eq!(locale.parse("1e5"), Ok(dec("100000")));
+        assert_eq!(locale.parse("-1.5E-2"), Ok(dec("-0.015")));
+        assert_eq!(NumberLocale::LV.parse("2,5e3"), Ok(dec("2500")));
+        assert_eq!(locale.parse("1e"), Err(NumberError("1e".to_string())));
+        assert_eq!(locale.parse("+-1"), Err(NumberError("+-1".to_string())));
+    }
+
+    #[test]
+    fn format_numbers() {
+        assert_eq!(NumberLocale::EN.format(dec("1234.5")), "1234.5");
+        assert_eq!(NumberLocale::LV.format(dec("1234.5")), "1234,5");
+        assert_eq!(NumberLocale::LV.format(dec("1.10")), "1,10");
+        assert_eq!(NumberLocale::LV.normalize("0,10"), Ok("0.10".to_string()));
+    }
+}
@@ -3,10 +3,13 @@ use std::fs::File;
 use std::io::{BufReader, BufWriter};
 
 use crate::bdt::Bdt;
+use crate::bdt_io::{BdtSink, BdtSource};
 
+/// JSON bundle of tables
 pub struct JsonAdapter {}
 
 impl JsonAdapter {
+    /// Writes tables to JSON file
     pub fn write_bdt(value: Vec<Bdt>, path: &str) -> Result<(), Box<dyn Error>> {
         let output_file = File::create(path)?;
         let mut writer = BufWriter::new(output_file);
@@ -14,10 +17,24 @@ impl JsonAdapter {
         Ok(())
     }
 
+    /// Reads tables from JSON file, numbers are rescaled to column scale
     pub fn read_bdt(path: &str) -> Result<Vec<Bdt>, Box<dyn Error>> {
         let file = File::open(path)?;
         let reader = BufReader::new(file);
-        let result: Vec<Bdt> = serde_json::from_reader(reader)?;
+        let mut result: Vec<Bdt> = serde_json::from_reader(reader)?;
+        result.iter_mut().for_each(Bdt::apply_scale);
         Ok(result)
     }
 }
+
+impl BdtSource for JsonAdapter {
+    fn read(&self, location: &str) -> Result<Vec<Bdt>, Box<dyn Error>> {
+        JsonAdapter::read_bdt(location)
+    }
+}
+
+impl BdtSink for JsonAdapter {
+    fn write(&self, tables: Vec<Bdt>, location: &str) -> Result<(), Box<dyn Error>> {
+        JsonAdapter::write_bdt(tables, location)
+    }
+}
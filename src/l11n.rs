@@ -1 +1,2 @@
+/// Languages
 pub mod language;
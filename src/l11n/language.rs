@@ -1,12 +1,17 @@
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
+/// Language of table names
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Default)]
 pub enum Language {
+    /// English
     #[default]
     EN,
+    /// Latvian
     LV,
+    /// Polish
     PL,
+    /// Lithuanian
     LT,
 }
 
@@ -0,0 +1,66 @@
+//! Balcia business data tables (BDT): model, readers and writers.
+//!
+//! A [`Bdt`] is a business table with names, typed columns and data rows. Tables are
+//! read by a [`BdtSource`] and written by a [`BdtSink`], the [`Registry`] holds built-in
+//! ones by format name:
+//!
+//! ```no_run
+//! use balcia_bdt_v2::{NumberLocale, Registry, RowFilter, TableFilter, Templates};
+//!
+//! let registry = Registry::with_defaults(NumberLocale::EN, "%d.%m.%Y", &Templates::default());
+//! let filter = TableFilter::new(vec!["TT0*".to_string(), "!TT02*".to_string()])?;
+//! let rows = RowFilter::new(vec!["valid_at(2024-01-01)".to_string()])?;
+//! registry.convert(&"csv:./data/TT".parse()?, &"json:TT.json".parse()?, &filter, &rows)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! CSV validation is done while reading: [`csv_adapter::CsvAdapter::with_options`] fails
+//! with located errors of rejected records in strict mode and reports spreadsheet artifacts
+//! as [`csv_adapter::csv_lint::Lint`]s.
+//!
+//! Optional backends are behind cargo features: `oracle` (reading from Oracle DB, needs
+//! Oracle client libraries), `sqlite` (SQLite load scripts), `parquet` and `xlsx`.
+
+#![warn(missing_docs)]
+
+/// Business table model: tables, columns, typed values and skip flags
+pub mod bdt;
+/// Source and sink traits, endpoints, table filter and format registry
+pub mod bdt_io;
+/// Classifier entries loaded next to business tables
+pub mod classifier;
+/// CSV bundle of `tables.csv`, `columns.csv` and `data.csv`: reading, validation and writing
+pub mod csv_adapter;
+/// Wide CSV with one column per BDT column, as exported data
+pub mod csv_data_adapter;
+/// Date, number and encoding formats
+pub mod format;
+/// JSON bundle
+pub mod json_adapter;
+/// Languages of table names
+pub mod l11n;
+/// Liquibase changelog of Oracle load operations
+pub mod liquibase_adapter;
+/// Reading tables from Oracle DB
+#[cfg(feature = "oracle")]
+pub mod oracle_adapter;
+/// Parquet file per table
+#[cfg(feature = "parquet")]
+pub mod parquet_adapter;
+/// PostgreSQL DDL and COPY script
+pub mod postgres_adapter;
+/// Oracle SQL load script
+pub mod sql_adapter;
+/// SQLite load script
+#[cfg(feature = "sqlite")]
+pub mod sqlite_adapter;
+/// Handlebars templates of script writers, built-in or overridden from files
+pub mod template;
+/// XLSX workbook per table
+#[cfg(feature = "xlsx")]
+pub mod xlsx_adapter;
+
+pub use bdt::{Bdt, Column, RowValues, Skip};
+pub use bdt_io::{BdtSink, BdtSource, Endpoint, Registry, RowFilter, TableFilter};
+pub use format::number_locale::NumberLocale;
+pub use template::Templates;
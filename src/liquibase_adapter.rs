@@ -0,0 +1,266 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use handlebars::Handlebars;
+use serde::Serialize;
+
+use crate::bdt::Bdt;
+use crate::bdt_io::BdtSink;
+use crate::sql_adapter::{dependency_order, oracle_date_format, rollback_split, SqlOptions};
+use crate::template::helpers::{
+    data_field_helper, date_helper, escape_xml, oracle_literal, oracle_literal_helper, yn_helper,
+};
+
+const AUTHOR: &str = "balcia-bdt";
+const XMLNS: &str = "http://www.liquibase.org/xml/ns/dbchangelog";
+const XSD: &str = "http://www.liquibase.org/xml/ns/dbchangelog/dbchangelog-4.9.xsd";
+
+/// Data of `liquibase.hbs`, PL/SQL block loading or removing one table
+#[derive(Debug, Serialize)]
+struct TableBlock<'a> {
+    date_format: &'a str,
+    decimal_separator: char,
+    /// `ALTER SESSION` statements as literals for `EXECUTE IMMEDIATE`
+    session: &'a [String],
+    remove: bool,
+    bdt: &'a Bdt,
+}
+
+/// 64-bit FNV-1a hash, stable across platforms and releases
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Escapes text content of XML element, keeping quotes and `=>` readable
+fn xml_text(text: &str) -> String {
+    text.trim_end()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace("]]>", "]]&gt;")
+}
+
+/// Liquibase XML changelog with one changeSet per table, in dependency order. Changes
+/// load tables like Oracle script. With baseline, rollbacks restore baseline tables or
+/// remove new ones, without it changeSets have no rollback.
+pub struct LiquibaseWriter {
+    options: SqlOptions,
+    baseline: Option<Vec<Bdt>>,
+}
+
+impl LiquibaseWriter {
+    /// Writer of changelog without rollbacks
+    pub fn new(options: SqlOptions) -> Self {
+        LiquibaseWriter {
+            options,
+            baseline: None,
+        }
+    }
+
+    /// Tables state to return to on rollback, tables missing in it are removed
+    pub fn with_baseline(mut self, baseline: Vec<Bdt>) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+
+    /// Writes changelog of tables to file
+    pub fn write_bdt(&self, tables: Vec<Bdt>, filename: &str) -> Result<(), Box<dyn Error>> {
+        let file = File::create(filename)?;
+        let mut writer = BufWriter::new(file);
+        self.render(tables, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn render<W: Write>(&self, tables: Vec<Bdt>, mut writer: W) -> Result<(), Box<dyn Error>> {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_escape_fn(handlebars::no_escape);
+        handlebars.register_helper("yn", Box::new(yn_helper));
+        handlebars.register_helper("df", Box::new(data_field_helper));
+        handlebars.register_helper("date", Box::new(date_helper));
+        handlebars.register_helper("sql", Box::new(oracle_literal_helper));
+        self.options
+            .templates
+            .register(&mut handlebars, "liquibase.hbs")?;
+
+        let SqlOptions { dates, numbers, .. } = &self.options;
+        let session = [
+            format!(
+                "ALTER SESSION SET NLS_NUMERIC_CHARACTERS = {}",
+                oracle_literal(numbers.nls_numeric_characters())
+            ),
+            format!(
+                "ALTER SESSION SET NLS_DATE_FORMAT = {}",
+                oracle_literal(&oracle_date_format(&dates.output_format)?)
+            ),
+        ]
+        .map(|statement| oracle_literal(&statement));
+        let block = |bdt: &Bdt, remove: bool| {
+            let data = TableBlock {
+                date_format: &dates.output_format,
+                decimal_separator: numbers.decimal_separator(),
+                session: &session,
+                remove,
+                bdt,
+            };
+            handlebars.render("template", &data)
+        };
+
+        let tables = dependency_order(tables.into_iter().filter_map(Bdt::deployable).collect())?;
+        let split = self
+            .baseline
+            .as_ref()
+            .map(|baseline| rollback_split(tables.clone(), baseline.clone()));
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            writer,
+            r#"<databaseChangeLog xmlns="{0}" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="{0} {1}">"#,
+            XMLNS, XSD
+        )?;
+        for bdt in tables.iter() {
+            let change = block(bdt, false)?;
+            let rollback = match &split {
+                None => None,
+                Some((restore, remove)) => {
+                    Some(match restore.iter().find(|base| base.ic == bdt.ic) {
+                        Some(base) => block(base, false)?,
+                        None => block(bdt, remove.iter().any(|new| new.ic == bdt.ic))?,
+                    })
+                }
+            };
+            let name = bdt
+                .names
+                .names()
+                .first()
+                .map(|name| name.name.as_str())
+                .unwrap_or_default();
+            writeln!(writer)?;
+            writeln!(
+                writer,
+                r#"    <changeSet id="{}" author="{}">"#,
+                escape_xml(&format!("{}-{:016x}", bdt.ic, fnv1a(&change))),
+                AUTHOR
+            )?;
+            writeln!(writer, "        <comment>{}</comment>", escape_xml(name))?;
+            writeln!(
+                writer,
+                r#"        <sql dbms="oracle" splitStatements="false" stripComments="false">{}</sql>"#,
+                xml_text(&change)
+            )?;
+            if let Some(rollback) = rollback {
+                writeln!(writer, "        <rollback>")?;
+                writeln!(
+                    writer,
+                    r#"            <sql dbms="oracle" splitStatements="false" stripComments="false">{}</sql>"#,
+                    xml_text(&rollback)
+                )?;
+                writeln!(writer, "        </rollback>")?;
+            }
+            writeln!(writer, "    </changeSet>")?;
+        }
+        writeln!(writer, "</databaseChangeLog>")?;
+        Ok(())
+    }
+}
+
+impl BdtSink for LiquibaseWriter {
+    fn write(&self, tables: Vec<Bdt>, location: &str) -> Result<(), Box<dyn Error>> {
+        self.write_bdt(tables, location)
+    }
+
+    fn replaces_data(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bdt::Skip;
+    use crate::format::date_policy::DatePolicy;
+    use crate::json_adapter::JsonAdapter;
+
+    use super::*;
+
+    fn changelog(tables: Vec<Bdt>, baseline: Option<Vec<Bdt>>) -> String {
+        let options = SqlOptions {
+            dates: DatePolicy::new(vec![], "%d.%m.%Y"),
+            ..Default::default()
+        };
+        let mut writer = LiquibaseWriter::new(options);
+        if let Some(baseline) = baseline {
+            writer = writer.with_baseline(baseline);
+        }
+        let mut out = Vec::new();
+        writer.render(tables, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn fnv1a_test() {
+        assert_eq!(fnv1a(""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a("a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a("foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn changeset_per_table_with_stable_ids() {
+        let tables = JsonAdapter::read_bdt("./data/TT/TT.json").unwrap();
+        let xml = changelog(tables, None);
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<databaseChangeLog "));
+        assert!(xml.ends_with("</databaseChangeLog>\n"));
+        assert_eq!(xml.matches("<changeSet ").count(), 4);
+        assert!(!xml.contains("'TT_CONFIG'"));
+        assert!(xml.contains("<changeSet id=\"TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE-"));
+        assert!(
+            xml.contains("EXECUTE IMMEDIATE 'ALTER SESSION SET NLS_DATE_FORMAT = ''DD.MM.YYYY''';")
+        );
+        assert!(xml.contains(
+            "    cfgload.store_ctb_data(\n        '',\n        'TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE',\n"
+        ));
+        assert!(!xml.contains("<rollback>"), "no rollback without baseline");
+        let removing = changelog(
+            JsonAdapter::read_bdt("./data/TT/TT.json").unwrap(),
+            Some(vec![]),
+        );
+        assert!(removing.contains("delete from agc_custom_table_type ctt where ctt.id = f.get_cdf_id('AGC_CUSTOM_TABLE_TYPE','TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE');"));
+
+        let ids = |xml: &str| -> Vec<String> {
+            xml.lines()
+                .filter_map(|line| line.trim().strip_prefix("<changeSet id=\""))
+                .map(|line| line.split('"').next().unwrap().to_string())
+                .collect()
+        };
+        let mut tables = JsonAdapter::read_bdt("./data/TT/TT.json").unwrap();
+        let first = ids(&xml);
+        assert_eq!(
+            ids(&changelog(
+                JsonAdapter::read_bdt("./data/TT/TT.json").unwrap(),
+                None
+            )),
+            first
+        );
+        tables[4].data[0].values[0].value =
+            crate::bdt::column_value::ColumnValueType::Num(Some(42.into()));
+        let changed = ids(&changelog(tables, None));
+        assert_eq!(changed[..3], first[..3]);
+        assert_ne!(changed[3], first[3]);
+    }
+
+    #[test]
+    fn rollback_restores_baseline() {
+        let mut tables = JsonAdapter::read_bdt("./data/TT/TT.json").unwrap();
+        tables.retain(|bdt| bdt.ic == "TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE");
+        let mut baseline = tables.clone();
+        baseline[0].skip = Skip::Deploy;
+        baseline[0].data.truncate(1);
+        baseline[0].columns[0].title = "Age <from> & more".to_string();
+        let xml = changelog(tables, Some(baseline));
+        let rollback = xml.split("<rollback>").nth(1).unwrap();
+        assert_eq!(rollback.matches("cfgload.store_ctb_data(").count(), 1);
+        assert!(rollback.contains("'Age &lt;from> '||chr(38)||' more'"));
+        assert!(rollback.contains("P_IC=>'TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE'"));
+        assert!(!rollback.contains("ctt.id"));
+    }
+}
@@ -1,24 +1,37 @@
-mod bdt;
-mod classifier;
-mod csv_adapter;
-mod csv_data_adapter;
-mod format;
-mod json_adapter;
-mod l11n;
-#[cfg(feature = "oracle")]
-mod oracle_adapter;
-mod sql_adapter;
-mod sqlite_adapter;
-
 use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
 
+use balcia_bdt_v2::bdt::Bdt;
+use balcia_bdt_v2::bdt_io::{BdtSource, Endpoint, Registry, RowFilter, TableFilter};
+#[cfg(feature = "sqlite")]
+use balcia_bdt_v2::classifier::ClassifierRecord;
+use balcia_bdt_v2::csv_adapter::csv_dialect::{CsvDialect, LineTerminator};
+use balcia_bdt_v2::csv_adapter::csv_error::ParseMode;
+#[cfg(feature = "sqlite")]
+use balcia_bdt_v2::csv_adapter::CsvReader;
+use balcia_bdt_v2::csv_adapter::{CsvAdapter, CsvOptions, CsvSource, CsvWriter};
+use balcia_bdt_v2::csv_data_adapter;
+use balcia_bdt_v2::format::date_policy::DatePolicy;
+use balcia_bdt_v2::format::encoding::{self, OutputEncoding};
+use balcia_bdt_v2::format::lv_date_format;
+use balcia_bdt_v2::format::number_locale::NumberLocale;
+use balcia_bdt_v2::json_adapter::JsonAdapter;
+use balcia_bdt_v2::liquibase_adapter::LiquibaseWriter;
+#[cfg(feature = "oracle")]
+use balcia_bdt_v2::oracle_adapter;
+#[cfg(feature = "parquet")]
+use balcia_bdt_v2::parquet_adapter::ParquetAdapter;
+use balcia_bdt_v2::postgres_adapter::PostgresAdapter;
+use balcia_bdt_v2::sql_adapter::{self, Plan, SqlMode, SqlOptions};
+#[cfg(feature = "sqlite")]
+use balcia_bdt_v2::sqlite_adapter::SqliteAdapter;
+use balcia_bdt_v2::template::{self, Templates};
+#[cfg(feature = "xlsx")]
+use balcia_bdt_v2::xlsx_adapter::XlsxAdapter;
 use clap::{Args, Parser, Subcommand};
-use csv_adapter::CsvWriter;
-
-use crate::bdt::Bdt;
-use crate::csv_adapter::CsvAdapter;
-use crate::json_adapter::JsonAdapter;
-use crate::sqlite_adapter::SqliteAdapter;
+use encoding_rs::Encoding;
 
 /// Convert BDT from one format to other
 #[derive(Parser, Debug)]
@@ -41,7 +54,24 @@ pub enum Adapter {
     /// Write business tables from stdin as SQL scripts
     Sql(SqlCommand),
     /// Write to stdout sqlite load script from stdin JSON bdt
+    #[cfg(feature = "sqlite")]
     Sqlite(SqliteCommand),
+    /// Write PostgreSQL DDL and COPY data script from JSON bdt
+    Postgres(PostgresCommand),
+    /// Write Liquibase changelog with a changeSet per table from JSON bdt
+    Liquibase(LiquibaseCommand),
+    /// Write each business table from JSON bdt to Parquet file
+    #[cfg(feature = "parquet")]
+    Parquet(ParquetCommand),
+    /// Write each business table from JSON bdt to XLSX workbook
+    #[cfg(feature = "xlsx")]
+    Xlsx(XlsxCommand),
+    /// Read business tables from any source format and write them to any sink format
+    Convert(ConvertCommand),
+    /// Write built-in templates to directory for customization with --template-dir
+    Templates(TemplatesCommand),
+    /// Render custom handlebars template with business tables, e.g. documentation page
+    Render(RenderCommand),
 }
 
 #[derive(Debug, Args)]
@@ -66,6 +96,84 @@ pub struct CsvReadCommand {
     path: String,
     /// json output file path with filename
     filename: String,
+    /// strict fails on any rejected record, lenient writes them to *.rejected.csv files
+    #[clap(long, value_enum, default_value_t = ParseMode::Strict)]
+    mode: ParseMode,
+    /// accepted input date format, can be repeated (default: %d.%m.%Y, %Y-%m-%d, %d/%m/%Y, %m/%d/%Y)
+    #[clap(long)]
+    input_date_format: Vec<String>,
+    /// decimal and thousands separators of numbers
+    #[clap(long, value_enum, default_value_t = NumberLocale::EN)]
+    locale: NumberLocale,
+    /// encoding of files without BOM, like windows-1257 (default: detected)
+    #[clap(long, value_parser = encoding::parse_label)]
+    encoding: Option<&'static Encoding>,
+    #[clap(flatten)]
+    dialect: DialectArgs,
+    /// fail on spreadsheet artifacts like `#NAME?` values or unknown headers
+    #[clap(long)]
+    deny_warnings: bool,
+    /// tables to read: IC, glob like TT0*, re:<regex>, !<excluded> or @<file> listing them
+    #[clap(long = "table")]
+    tables: Vec<String>,
+    /// row filter like `VALID_TO is null`, `CONFIG_TYPE = 'GC_SERIES'` or `valid_at(2024-01-01)`,
+    /// can be repeated
+    #[clap(long = "where")]
+    filters: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct DialectArgs {
+    /// field delimiter, `tab` for tab (default: sniffed when reading, comma when writing)
+    #[clap(long, value_parser = parse_byte)]
+    delimiter: Option<u8>,
+    /// quote character
+    #[clap(long, value_parser = parse_byte, default_value = "\"")]
+    quote: u8,
+    /// line terminator of written files
+    #[clap(long, value_enum, default_value_t = LineTerminator::Lf)]
+    terminator: LineTerminator,
+    /// files have no header line, fields are in BDT column order
+    #[clap(long)]
+    no_header: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct TemplateArgs {
+    /// handlebars template file used instead of the built-in one
+    #[clap(long)]
+    template: Option<PathBuf>,
+    /// directory with templates overriding built-in ones of the same name
+    #[clap(long)]
+    template_dir: Option<PathBuf>,
+}
+
+impl TemplateArgs {
+    fn templates(&self) -> Templates {
+        Templates {
+            file: self.template.clone(),
+            dir: self.template_dir.clone(),
+        }
+    }
+}
+
+impl DialectArgs {
+    fn dialect(&self) -> CsvDialect {
+        CsvDialect {
+            delimiter: self.delimiter,
+            quote: self.quote,
+            terminator: self.terminator,
+            header: !self.no_header,
+        }
+    }
+}
+
+fn parse_byte(value: &str) -> Result<u8, String> {
+    match value {
+        "tab" => Ok(b'\t'),
+        _ if value.len() == 1 && value.is_ascii() => Ok(value.as_bytes()[0]),
+        _ => Err(format!("'{}' is not a single ASCII character", value)),
+    }
 }
 
 #[derive(Debug, Args)]
@@ -74,6 +182,24 @@ pub struct CsvWriteCommand {
     filename: String,
     /// path to csv file directory
     path: String,
+    /// tables to write: IC, glob like TT0*, re:<regex>, !<excluded> or @<file> listing them
+    #[clap(long = "table")]
+    tables: Vec<String>,
+    /// row filter like `VALID_TO is null`, `CONFIG_TYPE = 'GC_SERIES'` or `valid_at(2024-01-01)`,
+    /// can be repeated
+    #[clap(long = "where")]
+    filters: Vec<String>,
+    /// decimal separator of written numbers
+    #[clap(long, value_enum, default_value_t = NumberLocale::EN)]
+    locale: NumberLocale,
+    /// encoding of written files, like windows-1257
+    #[clap(long, value_parser = encoding::parse_label, default_value = "utf-8")]
+    encoding: &'static Encoding,
+    /// start written UTF-8 files with BOM, so Excel recognizes the encoding
+    #[clap(long)]
+    bom: bool,
+    #[clap(flatten)]
+    dialect: DialectArgs,
 }
 
 #[derive(Debug, Args)]
@@ -82,27 +208,112 @@ pub struct CsvDataCommand {
     filename: String,
     /// path to csv file directory
     path: String,
-    /// Name of the table to extrace
+    /// table to extract, IC or selection matching exactly one table
     table: String,
+    /// row filter like `VALID_TO is null`, `CONFIG_TYPE = 'GC_SERIES'` or `valid_at(2024-01-01)`,
+    /// can be repeated
+    #[clap(long = "where")]
+    filters: Vec<String>,
+    /// output date format
+    #[clap(long, default_value = csv_data_adapter::FORMAT)]
+    date_format: String,
+    /// decimal separator of written numbers
+    #[clap(long, value_enum, default_value_t = NumberLocale::EN)]
+    locale: NumberLocale,
+    /// encoding of written files, like windows-1257
+    #[clap(long, value_parser = encoding::parse_label, default_value = "utf-8")]
+    encoding: &'static Encoding,
+    /// start written UTF-8 files with BOM, so Excel recognizes the encoding
+    #[clap(long)]
+    bom: bool,
+    #[clap(flatten)]
+    dialect: DialectArgs,
 }
 
 #[derive(Debug, Args)]
 #[cfg(feature = "oracle")]
 pub struct OracleCommand {
-    /// business table IC code or LIKE mask
-    table_ic_code: String,
+    /// business tables: IC, LIKE mask or glob, re:<regex>, !<excluded> or @<file>,
+    /// comma separated
+    tables: String,
     /// json output file path with filename
     filename: String,
+    /// row filter like `VALID_TO is null`, `CONFIG_TYPE = 'GC_SERIES'` or `valid_at(2024-01-01)`,
+    /// can be repeated
+    #[clap(long = "where")]
+    filters: Vec<String>,
 }
 
 #[derive(Debug, Args)]
+#[cfg(feature = "sqlite")]
 pub struct SqliteCommand {
     /// json output file path with filename
     filename: String,
     /// sql output file full path
     sqlfile: String,
-    /// (optional) business table IC code to filter out
-    table_ic_code: Option<String>,
+    /// (optional) business tables: IC, glob like TT0*, re:<regex>, !<excluded> or @<file>
+    tables: Vec<String>,
+    /// row filter like `VALID_TO is null`, `CONFIG_TYPE = 'GC_SERIES'` or `valid_at(2024-01-01)`,
+    /// can be repeated
+    #[clap(long = "where")]
+    filters: Vec<String>,
+    /// classifier CSV file to load into bdt_classifiers table
+    #[clap(long)]
+    classifiers: Option<String>,
+    /// date format of metadata tables, same as `csv data` uses for imported files
+    #[clap(long, default_value = csv_data_adapter::FORMAT)]
+    date_format: String,
+    #[clap(flatten)]
+    templates: TemplateArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct PostgresCommand {
+    /// json input file path with filename
+    filename: String,
+    /// sql output file full path
+    sqlfile: String,
+    /// (optional) business tables: IC, glob like TT0*, re:<regex>, !<excluded> or @<file>
+    tables: Vec<String>,
+    /// row filter like `VALID_TO is null`, `CONFIG_TYPE = 'GC_SERIES'` or `valid_at(2024-01-01)`,
+    /// can be repeated
+    #[clap(long = "where")]
+    filters: Vec<String>,
+    /// also load BDT names and column definitions into metadata tables
+    #[clap(long)]
+    metadata: bool,
+    #[clap(flatten)]
+    templates: TemplateArgs,
+}
+
+#[derive(Debug, Args)]
+#[cfg(feature = "parquet")]
+pub struct ParquetCommand {
+    /// json input file path with filename
+    filename: String,
+    /// path to parquet file directory
+    path: String,
+    /// (optional) business tables: IC, glob like TT0*, re:<regex>, !<excluded> or @<file>
+    tables: Vec<String>,
+    /// row filter like `VALID_TO is null`, `CONFIG_TYPE = 'GC_SERIES'` or `valid_at(2024-01-01)`,
+    /// can be repeated
+    #[clap(long = "where")]
+    filters: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+#[cfg(feature = "xlsx")]
+pub struct XlsxCommand {
+    /// json input file path with filename
+    filename: String,
+    /// path to xlsx file directory
+    path: String,
+    /// (optional) business tables: IC, glob like TT0*, re:<regex>, !<excluded> or @<file>
+    tables: Vec<String>,
+    /// row filter like `VALID_TO is null`, `CONFIG_TYPE = 'GC_SERIES'` or `valid_at(2024-01-01)`,
+    /// can be repeated
+    #[clap(long = "where")]
+    filters: Vec<String>,
 }
 
 #[derive(Debug, Args)]
@@ -111,64 +322,358 @@ pub struct SqlCommand {
     filename: String,
     /// sql output file full path
     sqlfile: String,
-    /// (optional) business table IC code to filter out
-    table_ic_code: Option<String>,
+    /// (optional) business tables: IC, glob like TT0*, re:<regex>, !<excluded> or @<file>
+    tables: Vec<String>,
+    /// row filter like `VALID_TO is null`, `CONFIG_TYPE = 'GC_SERIES'` or `valid_at(2024-01-01)`,
+    /// can be repeated; load mode deletes rows left out, merge mode keeps them
+    #[clap(long = "where")]
+    filters: Vec<String>,
+    /// output date format, also set as NLS_DATE_FORMAT
+    #[clap(long, default_value = lv_date_format::FORMAT)]
+    date_format: String,
+    /// decimal separator of numbers, also set as NLS_NUMERIC_CHARACTERS
+    #[clap(long, value_enum, default_value_t = NumberLocale::EN)]
+    locale: NumberLocale,
+    /// `load` replaces columns and data of each table, `merge` merges them by key columns
+    /// and VALID_FROM, so that re-running script changes nothing
+    #[clap(long, value_enum, default_value_t = SqlMode::Load)]
+    mode: SqlMode,
+    /// in merge mode, end-date rows missing in bundle instead of deleting them
+    #[clap(long)]
+    end_date_missing: bool,
+    /// also write script restoring tables to their state in --baseline
+    #[clap(long, requires = "baseline")]
+    rollback: Option<String>,
+    /// bundle with current state of tables (JSON file or CSV directory), usually Oracle export,
+    /// for --rollback and --plan
+    #[clap(long)]
+    baseline: Option<String>,
+    /// print what script would do per table instead of writing it, with net effect against
+    /// --baseline when given
+    #[clap(long, conflicts_with = "rollback")]
+    plan: bool,
+    /// write one script per table into sqlfile directory, in dependency order, and
+    /// install.sql running them
+    #[clap(long)]
+    split: bool,
+    #[clap(flatten)]
+    templates: TemplateArgs,
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let args = Cli::parse();
+#[derive(Debug, Args)]
+pub struct LiquibaseCommand {
+    /// json input file path with filename
+    filename: String,
+    /// changelog XML output file
+    changelog: String,
+    /// (optional) business tables: IC, glob like TT0*, re:<regex>, !<excluded> or @<file>
+    tables: Vec<String>,
+    /// row filter like `VALID_TO is null`, `CONFIG_TYPE = 'GC_SERIES'` or `valid_at(2024-01-01)`,
+    /// can be repeated; changeSets delete rows left out
+    #[clap(long = "where")]
+    filters: Vec<String>,
+    /// output date format, also set as NLS_DATE_FORMAT
+    #[clap(long, default_value = lv_date_format::FORMAT)]
+    date_format: String,
+    /// decimal separator of numbers, also set as NLS_NUMERIC_CHARACTERS
+    #[clap(long, value_enum, default_value_t = NumberLocale::EN)]
+    locale: NumberLocale,
+    /// bundle with current state of tables (JSON file or CSV directory) restored by rollbacks,
+    /// tables missing in it are removed; without it changeSets have no rollback
+    #[clap(long)]
+    baseline: Option<String>,
+    #[clap(flatten)]
+    templates: TemplateArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct ConvertCommand {
+    /// source as <format>:<location>, formats: csv, json, oracle (table IC or LIKE mask)
+    #[clap(long)]
+    from: Endpoint,
+    /// sink as <format>:<location>, formats: csv, json, sql, liquibase, sqlite, postgres, parquet,
+    /// xlsx
+    #[clap(long)]
+    to: Endpoint,
+    /// tables to convert: IC, glob like TT0*, re:<regex>, !<excluded> or @<file> listing them
+    #[clap(long = "table")]
+    tables: Vec<String>,
+    /// row filter like `VALID_TO is null`, `CONFIG_TYPE = 'GC_SERIES'` or `valid_at(2024-01-01)`,
+    /// can be repeated
+    #[clap(long = "where")]
+    filters: Vec<String>,
+    /// decimal and thousands separators of read and written numbers
+    #[clap(long, value_enum, default_value_t = NumberLocale::EN)]
+    locale: NumberLocale,
+    /// output date format of SQL scripts
+    #[clap(long, default_value = lv_date_format::FORMAT)]
+    date_format: String,
+    /// drop trailing zeros of NUM values in columns without scale, like `30.0` in bundles
+    /// written by versions before exact decimals
+    #[clap(long)]
+    normalize_numbers: bool,
+    #[clap(flatten)]
+    templates: TemplateArgs,
+}
 
+#[derive(Debug, Args)]
+pub struct RenderCommand {
+    /// handlebars template file
+    template: PathBuf,
+    /// business tables as JSON file or CSV directory
+    bundle: String,
+    /// output file
+    out: String,
+    /// tables to render: IC, glob like TT0*, re:<regex>, !<excluded> or @<file> listing them
+    #[clap(long = "table")]
+    tables: Vec<String>,
+    /// row filter like `VALID_TO is null`, `CONFIG_TYPE = 'GC_SERIES'` or `valid_at(2024-01-01)`,
+    /// can be repeated
+    #[clap(long = "where")]
+    filters: Vec<String>,
+    /// date format used by `date` and `df` helpers (default: as in bundle)
+    #[clap(long)]
+    date_format: Option<String>,
+    /// decimal separator used by `df` helper (default: `.`)
+    #[clap(long, value_enum)]
+    locale: Option<NumberLocale>,
+}
+
+#[derive(Debug, Args)]
+pub struct TemplatesCommand {
+    /// output directory
+    path: PathBuf,
+    /// overwrite existing template files
+    #[clap(long)]
+    force: bool,
+}
+
+/// Keeps selected tables and rows, warning about selection items which matched nothing
+fn select(
+    items: &[String],
+    filters: &[String],
+    tables: Vec<Bdt>,
+) -> Result<Vec<Bdt>, Box<dyn Error>> {
+    let filter = TableFilter::new(items.to_vec())?;
+    warn_unmatched(&filter, &tables);
+    RowFilter::new(filters.to_vec())?.apply(filter.apply(tables))
+}
+
+/// Reads business tables from JSON file or CSV directory
+fn read_bundle(path: &str) -> Result<Vec<Bdt>, Box<dyn Error>> {
+    if Path::new(path).is_dir() {
+        CsvSource::default().read(path)
+    } else {
+        JsonAdapter::read_bdt(path)
+    }
+}
+
+fn warn_unmatched(filter: &TableFilter, tables: &[Bdt]) {
+    for item in filter.unmatched(tables.iter().map(|bdt| bdt.ic.as_str())) {
+        eprintln!("warning: table selection '{}' matched nothing", item);
+    }
+}
+
+fn main() {
+    if let Err(e) = run(Cli::parse()) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Cli) -> Result<(), Box<dyn Error>> {
     match &args.command {
         Adapter::Csv(args) => match &args.subcommand {
             CsvSubCommand::Read(args) => {
-                let adapter = CsvAdapter::new(String::from(&args.path));
-                let v: Vec<Bdt> = adapter.collect();
+                let options = CsvOptions {
+                    mode: args.mode,
+                    dates: DatePolicy::new(args.input_date_format.clone(), lv_date_format::FORMAT),
+                    numbers: args.locale,
+                    encoding: args.encoding,
+                    dialect: args.dialect.dialect(),
+                };
+                let adapter = CsvAdapter::with_options(String::from(&args.path), &options)?;
+                for lint in adapter.lints() {
+                    eprintln!("warning: {}", lint);
+                }
+                if args.deny_warnings && !adapter.lints().is_empty() {
+                    return Err(format!("{} CSV warning(s)", adapter.lints().len()).into());
+                }
+                let v = select(&args.tables, &args.filters, adapter.collect())?;
                 JsonAdapter::write_bdt(v, &args.filename)?;
             }
             CsvSubCommand::Write(args) => {
-                let v: Vec<Bdt> = JsonAdapter::read_bdt(&args.filename)?;
-                let writer = CsvWriter::new();
+                let v = select(
+                    &args.tables,
+                    &args.filters,
+                    JsonAdapter::read_bdt(&args.filename)?,
+                )?;
+                let writer = CsvWriter::with_numbers(args.locale)
+                    .with_encoding(OutputEncoding::new(args.encoding, args.bom)?)
+                    .with_dialect(args.dialect.dialect());
                 writer.write_bdt(v, String::from(&args.path))?;
             }
             CsvSubCommand::Data(args) => {
-                let v: Vec<Bdt> = JsonAdapter::read_bdt(&args.filename)?;
-
-                if let Some(bdt) = v.into_iter().find(|bdt| bdt.ic == args.table) {
-                    csv_data_adapter::write_csv_data(&args.path, &bdt)?;
-                } else {
-                    panic!("Table with name: {} not found.", args.table)
+                let tables = JsonAdapter::read_bdt(&args.filename)?;
+                let mut v = select(std::slice::from_ref(&args.table), &args.filters, tables)?;
+                if v.len() != 1 {
+                    let ics: Vec<&str> = v.iter().map(|bdt| bdt.ic.as_str()).collect();
+                    return Err(format!(
+                        "table selection '{}' should match exactly one table, matched: {}",
+                        args.table,
+                        ics.join(", ")
+                    )
+                    .into());
                 }
+                let dates = DatePolicy::new(vec![], &args.date_format);
+                let encoding = OutputEncoding::new(args.encoding, args.bom)?;
+                csv_data_adapter::write_csv_data(
+                    &args.path,
+                    v.remove(0),
+                    &dates,
+                    args.locale,
+                    &encoding,
+                    &args.dialect.dialect(),
+                )?;
             }
         },
         #[cfg(feature = "oracle")]
         Adapter::Oracle(args) => {
-            let v: Vec<Bdt> = oracle_adapter::read_oracle(&args.table_ic_code)?;
+            let filter = TableFilter::new(vec![args.tables.clone()])?;
+            let rows = RowFilter::new(args.filters.clone())?;
+            let v = oracle_adapter::read_selection(&filter)?;
+            warn_unmatched(&filter, &v);
+            let v = rows.apply(v)?;
             JsonAdapter::write_bdt(v, &args.filename)?;
         }
+        #[cfg(feature = "sqlite")]
         Adapter::Sqlite(args) => {
             let v: Vec<Bdt> = JsonAdapter::read_bdt(&args.filename)?;
-            match &args.table_ic_code {
-                Some(table) => {
-                    let filtered: Vec<Bdt> =
-                        v.into_iter().filter(|flt| table.eq(&flt.ic)).collect();
-                    SqliteAdapter::write_bdt(filtered, &args.sqlfile)?;
-                }
-                None => {
-                    SqliteAdapter::write_bdt(v, &args.sqlfile)?;
-                }
-            }
+            let classifiers: Vec<ClassifierRecord> = match &args.classifiers {
+                Some(path) => CsvReader::read_strict(String::from(path))?,
+                None => Vec::new(),
+            };
+            let filtered = select(&args.tables, &args.filters, v)?;
+            SqliteAdapter::write_bdt(
+                filtered,
+                classifiers,
+                &args.sqlfile,
+                &args.date_format,
+                &args.templates.templates(),
+            )?;
+        }
+        Adapter::Postgres(args) => {
+            let v: Vec<Bdt> = JsonAdapter::read_bdt(&args.filename)?;
+            let filtered = select(&args.tables, &args.filters, v)?;
+            PostgresAdapter::write_bdt(
+                filtered,
+                args.metadata,
+                &args.sqlfile,
+                &args.templates.templates(),
+            )?;
+        }
+        #[cfg(feature = "parquet")]
+        Adapter::Parquet(args) => {
+            let v: Vec<Bdt> = JsonAdapter::read_bdt(&args.filename)?;
+            let filtered = select(&args.tables, &args.filters, v)?;
+            ParquetAdapter::write_bdt(filtered, &args.path)?;
+        }
+        #[cfg(feature = "xlsx")]
+        Adapter::Xlsx(args) => {
+            let v: Vec<Bdt> = JsonAdapter::read_bdt(&args.filename)?;
+            let filtered = select(&args.tables, &args.filters, v)?;
+            XlsxAdapter::write_bdt(filtered, &args.path)?;
         }
         Adapter::Sql(args) => {
             let v: Vec<Bdt> = JsonAdapter::read_bdt(&args.filename)?;
-            match &args.table_ic_code {
-                Some(table) => {
-                    let filtered: Vec<Bdt> =
-                        v.into_iter().filter(|flt| table.eq(&flt.ic)).collect();
-                    crate::sql_adapter::write_bdt(filtered, &args.sqlfile)?;
-                }
-                None => {
-                    crate::sql_adapter::write_bdt(v, &args.sqlfile)?;
-                }
+            if args.end_date_missing && args.mode != SqlMode::Merge {
+                return Err("--end-date-missing needs --mode merge".into());
+            }
+            let options = SqlOptions {
+                dates: DatePolicy::new(vec![], &args.date_format),
+                numbers: args.locale,
+                mode: args.mode,
+                end_date_missing: args.end_date_missing,
+                rows: RowFilter::new(args.filters.clone())?,
+                templates: args.templates.templates(),
+            };
+            if args.mode == SqlMode::Load && !options.rows.is_empty() {
+                eprintln!("warning: load mode deletes rows left out by --where, use --mode merge to keep them");
+            }
+            if args.baseline.is_some() && args.rollback.is_none() && !args.plan {
+                return Err("--baseline needs --rollback or --plan".into());
+            }
+            let filtered = select(&args.tables, &args.filters, v)?;
+            if args.plan {
+                let baseline = args.baseline.as_deref().map(read_bundle).transpose()?;
+                print!("{}", Plan::new(filtered, baseline, &options)?);
+                return Ok(());
+            }
+            if let (Some(rollback), Some(baseline)) = (&args.rollback, &args.baseline) {
+                let baseline = read_bundle(baseline)?;
+                sql_adapter::write_rollback(filtered.clone(), baseline, rollback, &options)?;
+            }
+            if args.split {
+                let written =
+                    sql_adapter::write_split(filtered, Path::new(&args.sqlfile), &options)?;
+                eprintln!("{} file(s) written to {}", written.len(), args.sqlfile);
+            } else {
+                sql_adapter::write_bdt(filtered, &args.sqlfile, &options)?;
+            }
+        }
+        Adapter::Liquibase(args) => {
+            let v: Vec<Bdt> = JsonAdapter::read_bdt(&args.filename)?;
+            let options = SqlOptions {
+                dates: DatePolicy::new(vec![], &args.date_format),
+                numbers: args.locale,
+                templates: args.templates.templates(),
+                ..Default::default()
+            };
+            if !args.filters.is_empty() {
+                eprintln!("warning: changeSets delete rows left out by --where");
+            }
+            let filtered = select(&args.tables, &args.filters, v)?;
+            let mut writer = LiquibaseWriter::new(options);
+            if let Some(baseline) = &args.baseline {
+                writer = writer.with_baseline(read_bundle(baseline)?);
+            }
+            writer.write_bdt(filtered, &args.changelog)?;
+        }
+        Adapter::Convert(args) => {
+            let registry = Registry::with_defaults(
+                args.locale,
+                &args.date_format,
+                &args.templates.templates(),
+            )
+            .with_normalized_numbers(args.normalize_numbers);
+            let filter = TableFilter::new(args.tables.clone())?;
+            let rows = RowFilter::new(args.filters.clone())?;
+            if !rows.is_empty() && registry.sink(&args.to.format)?.replaces_data() {
+                eprintln!(
+                    "warning: {} sink deletes rows left out by --where",
+                    args.to.format
+                );
+            }
+            let conversion = registry.convert(&args.from, &args.to, &filter, &rows)?;
+            for item in conversion.unmatched {
+                eprintln!("warning: table selection '{}' matched nothing", item);
+            }
+            eprintln!("{} table(s) written to {}", conversion.written, args.to);
+        }
+        Adapter::Render(args) => {
+            let v = select(&args.tables, &args.filters, read_bundle(&args.bundle)?)?;
+            let file = File::create(&args.out)?;
+            template::render(
+                &args.template,
+                v,
+                args.date_format.clone(),
+                args.locale,
+                BufWriter::new(file),
+            )?;
+        }
+        Adapter::Templates(args) => {
+            for file in template::dump(&args.path, args.force)? {
+                eprintln!("{}", file.display());
             }
         }
     }
@@ -1,18 +1,36 @@
+use std::error::Error;
 use std::str::FromStr;
 
 use chrono::NaiveDate;
 use dotenv::dotenv;
 use include_oracle_sql::{impl_sql, include_sql};
+use rust_decimal::Decimal;
 use sibyl as oracle;
 
 use crate::bdt::column_type::ColumnType;
 use crate::bdt::column_value::RowValues;
 use crate::bdt::table_name::{Name, NameList};
 use crate::bdt::*;
+use crate::bdt_io::{BdtSource, TableFilter};
 use crate::csv_adapter::csv_model::{ColumnRow, DataRow};
 
 include_sql!("sql/bdt.sql");
 
+/// Oracle database source of tables by IC
+pub struct OracleAdapter {}
+
+impl BdtSource for OracleAdapter {
+    /// Reads tables selected by location, see [`TableFilter`] for the syntax
+    fn read(&self, location: &str) -> Result<Vec<Bdt>, Box<dyn Error>> {
+        read_selection(&TableFilter::new(vec![location.to_string()])?)
+    }
+}
+
+/// Reads tables by LIKE masks covering the selection and keeps the selected ones
+pub fn read_selection(filter: &TableFilter) -> Result<Vec<Bdt>, Box<dyn Error>> {
+    Ok(filter.apply(read_oracle(&filter.like_masks())?))
+}
+
 const FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 const FORMAT_ORACLE: &str = "yyyy-mm-dd hh:mi:ss";
 
@@ -23,8 +41,21 @@ fn to_naive_date(date: Option<oracle::Date>) -> Option<NaiveDate> {
     Some(NaiveDate::parse_from_str(&date_str[..], FORMAT).expect("Error converting data"))
 }
 
-#[cfg(not(feature = "tokio"))]
-pub fn read_oracle(table_ic: &str) -> sibyl::Result<Vec<Bdt>> {
+/// Reads NUMBER as text so values keep their exact decimal digits, NUMBERs with more
+/// than 28 significant digits do not fit decimal and fail
+fn to_decimal(num: Option<oracle::Number>) -> sibyl::Result<Option<Decimal>> {
+    let Some(num) = num else {
+        return Ok(None);
+    };
+    // text minimum format uses session decimal character
+    let text = num.to_string("TM9")?.trim().replace(',', ".");
+    let decimal = Decimal::from_str(&text)
+        .map_err(|e| sibyl::Error::Interface(format!("NUMBER {}: {}", text, e)))?;
+    Ok(Some(decimal))
+}
+
+/// Reads tables matching any of LIKE masks, each table once
+pub fn read_oracle(masks: &[String]) -> sibyl::Result<Vec<Bdt>> {
     use crate::l11n::language::Language;
 
     let oracle = sibyl::env()?;
@@ -38,131 +69,137 @@ pub fn read_oracle(table_ic: &str) -> sibyl::Result<Vec<Bdt>> {
 
     let mut bdt_list: Vec<Bdt> = Vec::new();
 
-    session.get_tables(table_ic, |row| {
-        let ic: &str = row.get("IC")?;
-        let valid_from: Option<oracle::Date> = row.get("VALID_FROM")?;
-        let valid_to: Option<oracle::Date> = row.get("VALID_TO")?;
-        let mut bdt = Bdt {
-            skip: "".to_string(),
-            ic: ic.to_string(),
-            names: NameList::new(Vec::new()),
-            valid_from: to_naive_date(valid_from),
-            valid_to: to_naive_date(valid_to),
-            columns: Vec::new(),
-            data: Vec::new(),
-        };
-
-        session.get_table_names(ic, |row| {
-            let table_name = Name::new(
-                Language::from_str(row.get("LNG_CODE")?).unwrap(),
-                row.get("NAME").unwrap_or_default(),
-                row.get("PRINT_NAME").unwrap_or_default(),
-                row.get("SHORT_PRINT_NAME").unwrap_or_default(),
-            );
-            bdt.names.push(table_name);
-            Ok(())
-        })?;
-
-        session.business_table_column_definition(ic, |row| {
-            let is_key_str: &str = row.get("IS_KEY")?;
-            let cdf_ic: Option<String> = row.get("CODIFICATOR_IC")?;
-            let select_params: Option<String> = row.get("SELECT_PARAMS")?;
-            let options: Option<String> = row.get("OPTIONS")?;
-            let column = Column {
-                id: None,
-                skip: "".to_string(),
-                name: row.get("COL_NAME")?,
-                title: row.get("TITLE")?,
-                ref_code: row.get("REF_CODE")?,
-                sequence: row.get("SEQUENCE")?,
-                col_type: ColumnType::from(&ColumnRow {
-                    skip: "".to_string(),
-                    id: None,
-                    table_type_id: "".to_string(),
-                    title: "".to_string(),
-                    col_name: "".to_string(),
-                    ref_code: row.get("REF_CODE")?,
-                    adm_codificator_id: cdf_ic.unwrap_or_default(),
-                    sequence: None,
-                    is_key: "".to_string(),
-                    options: "".to_string(),
-                    select_params: select_params.unwrap_or_default(),
-                }),
-                is_key: "Y".eq(is_key_str),
-                options: options.unwrap_or_default(),
-            };
-            bdt.columns.push(column);
-            Ok(())
-        })?;
-
-        session.business_table_data(ic, |row| {
+    for mask in masks {
+        session.get_tables(mask, |row| {
+            let ic: &str = row.get("IC")?;
+            if bdt_list.iter().any(|bdt| bdt.ic == ic) {
+                return Ok(());
+            }
             let valid_from: Option<oracle::Date> = row.get("VALID_FROM")?;
             let valid_to: Option<oracle::Date> = row.get("VALID_TO")?;
-            let cdf1: Option<String> = row.get("CDF1_IC")?;
-            let cdf2: Option<String> = row.get("CDF2_IC")?;
-            let cdf3: Option<String> = row.get("CDF3_IC")?;
-            let cdf4: Option<String> = row.get("CDF4_IC")?;
-            let cdf5: Option<String> = row.get("CDF5_IC")?;
-            let cdf6: Option<String> = row.get("CDF6_IC")?;
-            let cdf7: Option<String> = row.get("CDF7_IC")?;
-            let cdf8: Option<String> = row.get("CDF8_IC")?;
-            let cdf9: Option<String> = row.get("CDF9_IC")?;
-            let cdf10: Option<String> = row.get("CDF10_IC")?;
-            let cdf11: Option<String> = row.get("CDF11_IC")?;
-            let cdf12: Option<String> = row.get("CDF12_IC")?;
-            let cdf13: Option<String> = row.get("CDF13_IC")?;
-            let cdf14: Option<String> = row.get("CDF14_IC")?;
-            let cdf15: Option<String> = row.get("CDF15_IC")?;
-            let text1: Option<String> = row.get("TEXT1")?;
-            let text2: Option<String> = row.get("TEXT2")?;
-            let text3: Option<String> = row.get("TEXT3")?;
-            let text4: Option<String> = row.get("TEXT4")?;
-            let text5: Option<String> = row.get("TEXT5")?;
-            let row = DataRow {
-                skip: "".to_string(),
-                id: row.get("ID")?,
-                table_type: bdt.ic.clone(),
+            let mut bdt = Bdt {
+                skip: Skip::default(),
+                ic: ic.to_string(),
+                names: NameList::new(Vec::new()),
                 valid_from: to_naive_date(valid_from),
                 valid_to: to_naive_date(valid_to),
-                cdf1: cdf1.unwrap_or_default(),
-                cdf2: cdf2.unwrap_or_default(),
-                cdf3: cdf3.unwrap_or_default(),
-                cdf4: cdf4.unwrap_or_default(),
-                cdf5: cdf5.unwrap_or_default(),
-                cdf6: cdf6.unwrap_or_default(),
-                cdf7: cdf7.unwrap_or_default(),
-                cdf8: cdf8.unwrap_or_default(),
-                cdf9: cdf9.unwrap_or_default(),
-                cdf10: cdf10.unwrap_or_default(),
-                cdf11: cdf11.unwrap_or_default(),
-                cdf12: cdf12.unwrap_or_default(),
-                cdf13: cdf13.unwrap_or_default(),
-                cdf14: cdf14.unwrap_or_default(),
-                cdf15: cdf15.unwrap_or_default(),
-                num1: row.get("NUM1")?,
-                num2: row.get("NUM2")?,
-                num3: row.get("NUM3")?,
-                num4: row.get("NUM4")?,
-                num5: row.get("NUM5")?,
-                num6: row.get("NUM6")?,
-                num7: row.get("NUM7")?,
-                num8: row.get("NUM8")?,
-                num9: row.get("NUM9")?,
-                num10: row.get("NUM10")?,
-                text1: text1.unwrap_or_default(),
-                text2: text2.unwrap_or_default(),
-                text3: text3.unwrap_or_default(),
-                text4: text4.unwrap_or_default(),
-                text5: text5.unwrap_or_default(),
+                columns: Vec::new(),
+                data: Vec::new(),
             };
-            let row_values = RowValues::from_data_row(&bdt.columns, &row);
-            bdt.data.push(row_values);
+
+            session.get_table_names(ic, |row| {
+                let table_name = Name::new(
+                    Language::from_str(row.get("LNG_CODE")?).unwrap(),
+                    row.get("NAME").unwrap_or_default(),
+                    row.get("PRINT_NAME").unwrap_or_default(),
+                    row.get("SHORT_PRINT_NAME").unwrap_or_default(),
+                );
+                bdt.names.push(table_name);
+                Ok(())
+            })?;
+
+            session.business_table_column_definition(ic, |row| {
+                let is_key_str: &str = row.get("IS_KEY")?;
+                let cdf_ic: Option<String> = row.get("CODIFICATOR_IC")?;
+                let select_params: Option<String> = row.get("SELECT_PARAMS")?;
+                let options: Option<String> = row.get("OPTIONS")?;
+                let column = Column {
+                    id: None,
+                    skip: Skip::default(),
+                    name: row.get("COL_NAME")?,
+                    title: row.get("TITLE")?,
+                    ref_code: row.get("REF_CODE")?,
+                    sequence: row.get("SEQUENCE")?,
+                    col_type: ColumnType::from(&ColumnRow {
+                        skip: Skip::default(),
+                        id: None,
+                        table_type_id: "".to_string(),
+                        title: "".to_string(),
+                        col_name: "".to_string(),
+                        ref_code: row.get("REF_CODE")?,
+                        adm_codificator_id: cdf_ic.unwrap_or_default(),
+                        sequence: None,
+                        is_key: "".to_string(),
+                        options: "".to_string(),
+                        select_params: select_params.unwrap_or_default(),
+                    }),
+                    is_key: "Y".eq(is_key_str),
+                    options: options.unwrap_or_default(),
+                };
+                bdt.columns.push(column);
+                Ok(())
+            })?;
+
+            session.business_table_data(ic, |row| {
+                let valid_from: Option<oracle::Date> = row.get("VALID_FROM")?;
+                let valid_to: Option<oracle::Date> = row.get("VALID_TO")?;
+                let cdf1: Option<String> = row.get("CDF1_IC")?;
+                let cdf2: Option<String> = row.get("CDF2_IC")?;
+                let cdf3: Option<String> = row.get("CDF3_IC")?;
+                let cdf4: Option<String> = row.get("CDF4_IC")?;
+                let cdf5: Option<String> = row.get("CDF5_IC")?;
+                let cdf6: Option<String> = row.get("CDF6_IC")?;
+                let cdf7: Option<String> = row.get("CDF7_IC")?;
+                let cdf8: Option<String> = row.get("CDF8_IC")?;
+                let cdf9: Option<String> = row.get("CDF9_IC")?;
+                let cdf10: Option<String> = row.get("CDF10_IC")?;
+                let cdf11: Option<String> = row.get("CDF11_IC")?;
+                let cdf12: Option<String> = row.get("CDF12_IC")?;
+                let cdf13: Option<String> = row.get("CDF13_IC")?;
+                let cdf14: Option<String> = row.get("CDF14_IC")?;
+                let cdf15: Option<String> = row.get("CDF15_IC")?;
+                let text1: Option<String> = row.get("TEXT1")?;
+                let text2: Option<String> = row.get("TEXT2")?;
+                let text3: Option<String> = row.get("TEXT3")?;
+                let text4: Option<String> = row.get("TEXT4")?;
+                let text5: Option<String> = row.get("TEXT5")?;
+                let row = DataRow {
+                    skip: Skip::default(),
+                    id: row.get("ID")?,
+                    table_type: bdt.ic.clone(),
+                    valid_from: to_naive_date(valid_from),
+                    valid_to: to_naive_date(valid_to),
+                    cdf1: cdf1.unwrap_or_default(),
+                    cdf2: cdf2.unwrap_or_default(),
+                    cdf3: cdf3.unwrap_or_default(),
+                    cdf4: cdf4.unwrap_or_default(),
+                    cdf5: cdf5.unwrap_or_default(),
+                    cdf6: cdf6.unwrap_or_default(),
+                    cdf7: cdf7.unwrap_or_default(),
+                    cdf8: cdf8.unwrap_or_default(),
+                    cdf9: cdf9.unwrap_or_default(),
+                    cdf10: cdf10.unwrap_or_default(),
+                    cdf11: cdf11.unwrap_or_default(),
+                    cdf12: cdf12.unwrap_or_default(),
+                    cdf13: cdf13.unwrap_or_default(),
+                    cdf14: cdf14.unwrap_or_default(),
+                    cdf15: cdf15.unwrap_or_default(),
+                    num1: to_decimal(row.get("NUM1")?)?,
+                    num2: to_decimal(row.get("NUM2")?)?,
+                    num3: to_decimal(row.get("NUM3")?)?,
+                    num4: to_decimal(row.get("NUM4")?)?,
+                    num5: to_decimal(row.get("NUM5")?)?,
+                    num6: to_decimal(row.get("NUM6")?)?,
+                    num7: to_decimal(row.get("NUM7")?)?,
+                    num8: to_decimal(row.get("NUM8")?)?,
+                    num9: to_decimal(row.get("NUM9")?)?,
+                    num10: to_decimal(row.get("NUM10")?)?,
+                    text1: text1.unwrap_or_default(),
+                    text2: text2.unwrap_or_default(),
+                    text3: text3.unwrap_or_default(),
+                    text4: text4.unwrap_or_default(),
+                    text5: text5.unwrap_or_default(),
+                };
+                let row_values = RowValues::from_data_row(&bdt.columns, &row)
+                    .map_err(|e| sibyl::Error::Interface(format!("{}: {}", bdt.ic, e)))?;
+                bdt.data.push(row_values);
+                Ok(())
+            })?;
+
+            bdt_list.push(bdt);
             Ok(())
         })?;
-
-        bdt_list.push(bdt);
-        Ok(())
-    })?;
+    }
 
     Ok(bdt_list)
 }
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_array::builder::{Date32Builder, Decimal128Builder, StringBuilder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema};
+use chrono::NaiveDate;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use rust_decimal::Decimal;
+
+use crate::bdt::column_type::ColumnType;
+use crate::bdt::column_value::ColumnValueType;
+use crate::bdt::{Bdt, Column};
+use crate::bdt_io::BdtSink;
+
+/// Parquet file per table with typed columns
+pub struct ParquetAdapter {}
+
+/// Maximum precision of Decimal128 values
+const PRECISION: u8 = 38;
+
+fn data_type(bdt: &Bdt, column: &Column) -> DataType {
+    match column.col_type {
+        ColumnType::Date => DataType::Date32,
+        ColumnType::Num => DataType::Decimal128(PRECISION, scale(bdt, column) as i8),
+        ColumnType::Text | ColumnType::Cdf { .. } => DataType::Utf8,
+    }
+}
+
+/// Configured column scale, otherwise the largest scale of column values
+fn scale(bdt: &Bdt, column: &Column) -> u32 {
+    column.scale().unwrap_or_else(|| {
+        column_values(bdt, column)
+            .filter_map(|value| match value {
+                Some(ColumnValueType::Num(Some(num))) => Some(num.scale()),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+    })
+}
+
+fn column_values<'a>(
+    bdt: &'a Bdt,
+    column: &'a Column,
+) -> impl Iterator<Item = Option<&'a ColumnValueType>> {
+    bdt.data.iter().map(|row| {
+        row.values
+            .iter()
+            .find(|value| value.ref_code == column.ref_code)
+            .map(|value| &value.value)
+    })
+}
+
+/// Mantissa of number at scale, none when number has more decimals than scale
+fn mantissa(num: Decimal, scale: u32) -> Option<i128> {
+    if num.normalize().scale() > scale {
+        return None;
+    }
+    let mut num = num;
+    num.rescale(scale);
+    Some(num.mantissa())
+}
+
+fn field(bdt: &Bdt, column: &Column) -> Field {
+    let mut metadata = HashMap::from([
+        ("title".to_string(), column.title.clone()),
+        ("ref_code".to_string(), column.ref_code.clone()),
+        ("is_key".to_string(), column.is_key.to_string()),
+    ]);
+    if let ColumnType::Cdf {
+        codificator_id,
+        select_params,
+    } = &column.col_type
+    {
+        metadata.insert("codificator_id".to_string(), codificator_id.clone());
+        metadata.insert("select_params".to_string(), select_params.clone());
+    }
+    Field::new(column.name.clone(), data_type(bdt, column), true).with_metadata(metadata)
+}
+
+fn schema(bdt: &Bdt) -> Schema {
+    let fields: Vec<Field> = bdt
+        .columns
+        .iter()
+        .map(|column| field(bdt, column))
+        .collect();
+    let mut metadata = HashMap::from([("ic".to_string(), bdt.ic.clone())]);
+    for name in bdt.names.names() {
+        let lang = format!("{:?}", name.lang).to_lowercase();
+        metadata.insert(format!("name_{}", lang), name.name.clone());
+        metadata.insert(format!("print_name_{}", lang), name.print_name.clone());
+    }
+    Schema::new_with_metadata(fields, metadata)
+}
+
+fn days_from_epoch(date: NaiveDate) -> i32 {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+    (date - epoch).num_days() as i32
+}
+
+fn column_array(bdt: &Bdt, column: &Column) -> Result<ArrayRef, Box<dyn Error>> {
+    let values = column_values(bdt, column);
+    match column.col_type {
+        ColumnType::Date => {
+            let mut builder = Date32Builder::new();
+            for value in values {
+                match value {
+                    Some(ColumnValueType::Date(Some(date))) => {
+                        builder.append_value(days_from_epoch(*date))
+                    }
+                    _ => builder.append_null(),
+                }
+            }
+            Ok(Arc::new(builder.finish()))
+        }
+        ColumnType::Num => {
+            let scale = scale(bdt, column);
+            let mut builder = Decimal128Builder::new();
+            for value in values {
+                match value {
+                    Some(ColumnValueType::Num(Some(num))) => {
+                        let mantissa = mantissa(*num, scale).ok_or_else(|| {
+                            format!(
+                                "Table {} column {} value {} has more decimals than scale {}",
+                                bdt.ic, column.name, num, scale
+                            )
+                        })?;
+                        builder.append_value(mantissa)
+                    }
+                    _ => builder.append_null(),
+                }
+            }
+            Ok(Arc::new(
+                builder
+                    .finish()
+                    .with_precision_and_scale(PRECISION, scale as i8)?,
+            ))
+        }
+        ColumnType::Text | ColumnType::Cdf { .. } => {
+            let mut builder = StringBuilder::new();
+            for value in values {
+                match value {
+                    Some(ColumnValueType::Text(text)) | Some(ColumnValueType::Cdf(text)) => {
+                        builder.append_value(text)
+                    }
+                    _ => builder.append_null(),
+                }
+            }
+            Ok(Arc::new(builder.finish()))
+        }
+    }
+}
+
+impl ParquetAdapter {
+    /// Writes each BDT to `<path>/<IC>.parquet`
+    pub fn write_bdt(tables: Vec<Bdt>, path: &str) -> Result<(), Box<dyn Error>> {
+        for bdt in tables.into_iter().filter_map(Bdt::deployable) {
+            let file = File::create(Path::new(path).join(format!("{}.parquet", bdt.ic)))?;
+            ParquetAdapter::write_table(&bdt, file)?;
+        }
+        Ok(())
+    }
+
+    fn write_table(bdt: &Bdt, file: File) -> Result<(), Box<dyn Error>> {
+        let schema = Arc::new(schema(bdt));
+        let columns: Vec<ArrayRef> = bdt
+            .columns
+            .iter()
+            .map(|column| column_array(bdt, column))
+            .collect::<Result<_, _>>()?;
+        let batch = RecordBatch::try_new(schema.clone(), columns)?;
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let mut writer = ArrowWriter::try_new(file, schema, Some(props))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+}
+
+impl BdtSink for ParquetAdapter {
+    fn write(&self, tables: Vec<Bdt>, location: &str) -> Result<(), Box<dyn Error>> {
+        ParquetAdapter::write_bdt(tables, location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Date32Type, Decimal128Type};
+    use arrow_array::Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use crate::bdt::Skip;
+    use crate::json_adapter::JsonAdapter;
+
+    use super::*;
+
+    #[test]
+    fn days_from_epoch_test() {
+        assert_eq!(
+            days_from_epoch(NaiveDate::from_ymd_opt(1970, 1, 2).unwrap()),
+            1
+        );
+        assert_eq!(
+            days_from_epoch(NaiveDate::from_ymd_opt(2017, 9, 1).unwrap()),
+            17410
+        );
+    }
+
+    #[test]
+    fn numbers_are_not_rounded_to_scale() -> Result<(), Box<dyn Error>> {
+        let num = |text: &str| text.parse::<Decimal>().unwrap();
+        assert_eq!(mantissa(num("1.50"), 1), Some(15));
+        assert_eq!(mantissa(num("2"), 2), Some(200));
+        assert_eq!(mantissa(num("1.25"), 1), None);
+
+        let mut bdt = JsonAdapter::read_bdt("./data/TT/TT.json")?
+            .into_iter()
+            .find(|bdt| bdt.ic == "TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE")
+            .unwrap();
+        bdt.columns[0].options = "scale=1".to_string();
+        let column = bdt.columns[0].clone();
+        assert!(column_array(&bdt, &column).is_ok());
+        let value = bdt.data[0]
+            .values
+            .iter_mut()
+            .find(|value| value.ref_code == column.ref_code)
+            .unwrap();
+        value.value = ColumnValueType::Num(Some(num("1.25")));
+        let error = column_array(&bdt, &column).unwrap_err().to_string();
+        assert!(error.contains("column AGE_FROM value 1.25"), "{}", error);
+        Ok(())
+    }
+
+    #[test]
+    fn write_and_read_parquet() -> Result<(), Box<dyn Error>> {
+        let tables = JsonAdapter::read_bdt("./data/TT/TT.json")?;
+        let dir = env::temp_dir().join(format!("bdt_parquet_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        ParquetAdapter::write_bdt(tables, dir.to_str().unwrap())?;
+        assert!(!dir.join("TT_CONFIG.parquet").exists());
+
+        let file = File::open(dir.join("TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE.parquet"))?;
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+        let schema = builder.schema().clone();
+        assert_eq!(
+            schema.metadata().get("ic").unwrap(),
+            "TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE"
+        );
+        let age_from = schema.field_with_name("AGE_FROM")?;
+        assert_eq!(age_from.data_type(), &DataType::Decimal128(38, 1));
+        assert_eq!(age_from.metadata().get("title").unwrap(), "Age from");
+        let read_only = schema.field_with_name("READ_ONLY")?;
+        assert_eq!(read_only.data_type(), &DataType::Utf8);
+        assert_eq!(read_only.metadata().get("codificator_id").unwrap(), "YN");
+
+        let batch = builder.build()?.next().unwrap()?;
+        assert_eq!(batch.num_rows(), 3);
+        let ages = batch.column(1).as_primitive::<Decimal128Type>();
+        assert_eq!(ages.value(2), 990);
+        let defaults = batch.column(3).as_string::<i32>();
+        assert!(defaults.is_null(1));
+        assert_eq!(defaults.value(2), "N");
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn date_columns_are_date32() -> Result<(), Box<dyn Error>> {
+        let mut tables = JsonAdapter::read_bdt("./data/TT/TT.json")?;
+        tables.retain(|bdt| bdt.ic == "TT_CONFIG");
+        tables[0].skip = Skip::Deploy;
+        for column in tables[0].columns.iter_mut() {
+            column.skip = Skip::Deploy;
+        }
+        let dir = env::temp_dir().join(format!("bdt_parquet_date_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        ParquetAdapter::write_bdt(tables, dir.to_str().unwrap())?;
+
+        let file = File::open(dir.join("TT_CONFIG.parquet"))?;
+        let batch = ParquetRecordBatchReaderBuilder::try_new(file)?
+            .build()?
+            .next()
+            .unwrap()?;
+        let valid_from = batch.column(0).as_primitive::<Date32Type>();
+        assert_eq!(batch.schema().field(0).data_type(), &DataType::Date32);
+        assert!((0..batch.num_rows()).any(|row| valid_from.is_valid(row)));
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
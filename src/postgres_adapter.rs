@@ -0,0 +1,285 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use chrono::NaiveDate;
+use handlebars::Handlebars;
+use serde::Serialize;
+
+use crate::bdt::column_type::ColumnType;
+use crate::bdt::column_value::ColumnValueType;
+use crate::bdt::{Bdt, Column, RowValues, Skip};
+use crate::bdt_io::BdtSink;
+use crate::template::Templates;
+
+const FORMAT: &str = "%Y-%m-%d";
+const NULL: &str = "\\N";
+
+/// PostgreSQL script sink
+#[derive(Default)]
+pub struct PostgresAdapter {
+    /// Templates of script
+    pub templates: Templates,
+}
+
+#[derive(Debug, Serialize)]
+struct PgColumn {
+    name: String,
+    pg_type: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct PgTable {
+    ic_literal: String,
+    name: String,
+    key_name: String,
+    columns: Vec<PgColumn>,
+    column_list: String,
+    keys: String,
+    load_data: bool,
+    rows: Vec<String>,
+    meta_table: String,
+    meta_names: Vec<String>,
+    meta_columns: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct PgBdtList {
+    metadata: bool,
+    tables: Vec<PgTable>,
+}
+
+/// Quotes identifier, BDT codes are lowercased so tables can be queried without quotes
+fn identifier(name: &str) -> String {
+    format!("\"{}\"", name.to_lowercase().replace('"', "\"\""))
+}
+
+fn literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn pg_type(col_type: &ColumnType) -> &'static str {
+    match col_type {
+        ColumnType::Date => "DATE",
+        ColumnType::Num => "NUMERIC",
+        ColumnType::Text | ColumnType::Cdf { .. } => "TEXT",
+    }
+}
+
+/// Escapes value for COPY text format
+fn copy_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn copy_date(date: Option<NaiveDate>) -> String {
+    match date {
+        Some(date) => date.format(FORMAT).to_string(),
+        None => NULL.to_string(),
+    }
+}
+
+fn copy_row(values: Vec<String>) -> String {
+    values.join("\t")
+}
+
+fn data_value(row: &RowValues, column: &Column) -> String {
+    let value = row
+        .values
+        .iter()
+        .find(|value| value.ref_code == column.ref_code);
+    match value.map(|value| &value.value) {
+        Some(ColumnValueType::Cdf(text)) | Some(ColumnValueType::Text(text)) => copy_value(text),
+        Some(ColumnValueType::Num(Some(num))) => num.to_string(),
+        Some(ColumnValueType::Date(date)) => copy_date(*date),
+        Some(ColumnValueType::Num(None)) | None => NULL.to_string(),
+    }
+}
+
+impl From<&Bdt> for PgTable {
+    fn from(bdt: &Bdt) -> Self {
+        let columns: Vec<PgColumn> = bdt
+            .columns
+            .iter()
+            .map(|column| PgColumn {
+                name: identifier(&column.name),
+                pg_type: pg_type(&column.col_type),
+            })
+            .collect();
+        let column_list: Vec<String> = columns.iter().map(|column| column.name.clone()).collect();
+        let keys: Vec<String> = bdt
+            .columns
+            .iter()
+            .filter(|column| column.is_key)
+            .map(|column| identifier(&column.name))
+            .collect();
+        let load_data = bdt.skip != Skip::Data;
+        let rows = if load_data {
+            bdt.data
+                .iter()
+                .map(|row| {
+                    copy_row(
+                        bdt.columns
+                            .iter()
+                            .map(|column| data_value(row, column))
+                            .collect(),
+                    )
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let meta_table = copy_row(vec![
+            copy_value(&bdt.ic),
+            copy_value(bdt.skip.as_str()),
+            copy_date(bdt.valid_from),
+            copy_date(bdt.valid_to),
+        ]);
+        let meta_names = bdt
+            .names
+            .names()
+            .iter()
+            .map(|name| {
+                copy_row(vec![
+                    copy_value(&bdt.ic),
+                    format!("{:?}", name.lang),
+                    copy_value(&name.name),
+                    copy_value(&name.print_name),
+                    copy_value(&name.short_print_name),
+                ])
+            })
+            .collect();
+        let meta_columns = bdt
+            .columns
+            .iter()
+            .map(|column| {
+                let (col_type, codificator_id, select_params) = match &column.col_type {
+                    ColumnType::Cdf {
+                        codificator_id,
+                        select_params,
+                    } => ("Cdf", copy_value(codificator_id), copy_value(select_params)),
+                    ColumnType::Date => ("Date", NULL.to_string(), NULL.to_string()),
+                    ColumnType::Num => ("Num", NULL.to_string(), NULL.to_string()),
+                    ColumnType::Text => ("Text", NULL.to_string(), NULL.to_string()),
+                };
+                copy_row(vec![
+                    copy_value(&bdt.ic),
+                    copy_value(&column.name),
+                    copy_value(&column.title),
+                    copy_value(&column.ref_code),
+                    col_type.to_string(),
+                    codificator_id,
+                    select_params,
+                    column
+                        .sequence
+                        .map_or(NULL.to_string(), |sequence| sequence.to_string()),
+                    if column.is_key { "Y" } else { "N" }.to_string(),
+                    copy_value(&column.options),
+                    copy_value(column.skip.as_str()),
+                ])
+            })
+            .collect();
+
+        PgTable {
+            ic_literal: literal(&bdt.ic),
+            name: identifier(&bdt.ic),
+            key_name: identifier(&format!("{}_key", bdt.ic)),
+            columns,
+            column_list: column_list.join(", "),
+            keys: keys.join(", "),
+            load_data,
+            rows,
+            meta_table,
+            meta_names,
+            meta_columns,
+        }
+    }
+}
+
+impl PostgresAdapter {
+    /// Writes PostgreSQL script of tables to file, with BDT metadata tables if `metadata`
+    pub fn write_bdt(
+        tables: Vec<Bdt>,
+        metadata: bool,
+        filename: &str,
+        templates: &Templates,
+    ) -> Result<(), Box<dyn Error>> {
+        let file = File::create(filename)?;
+        let mut writer = BufWriter::new(file);
+        PostgresAdapter::render(tables, metadata, templates, &mut writer)
+    }
+
+    fn render<W: Write>(
+        tables: Vec<Bdt>,
+        metadata: bool,
+        templates: &Templates,
+        writer: W,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_escape_fn(handlebars::no_escape);
+        templates.register(&mut handlebars, "postgres.hbs")?;
+        let bdtlist = PgBdtList {
+            metadata,
+            tables: tables
+                .into_iter()
+                .filter_map(Bdt::deployable)
+                .map(|bdt| PgTable::from(&bdt))
+                .collect(),
+        };
+        handlebars.render_to_write("template", &bdtlist, writer)?;
+
+        Ok(())
+    }
+}
+
+impl BdtSink for PostgresAdapter {
+    /// Writes table DDL and data without metadata tables
+    fn write(&self, tables: Vec<Bdt>, location: &str) -> Result<(), Box<dyn Error>> {
+        PostgresAdapter::write_bdt(tables, false, location, &self.templates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::json_adapter::JsonAdapter;
+
+    use super::*;
+
+    fn render_tt(metadata: bool) -> Result<String, Box<dyn Error>> {
+        let tables = JsonAdapter::read_bdt("./data/TT/TT.json")?;
+        let mut out = Vec::new();
+        PostgresAdapter::render(tables, metadata, &Templates::default(), &mut out)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn copy_value_test() {
+        assert_eq!(copy_value("a\tb\nc\\d"), "a\\tb\\nc\\\\d");
+    }
+
+    #[test]
+    fn identifier_test() {
+        assert_eq!(identifier("TT_CONFIG"), "\"tt_config\"");
+        assert_eq!(identifier("A\"B"), "\"a\"\"b\"");
+    }
+
+    #[test]
+    fn golden_postgres_script() -> Result<(), Box<dyn Error>> {
+        let expected = fs::read_to_string("./data/TT/postgres.sql")?;
+        assert_eq!(render_tt(false)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn golden_postgres_script_with_metadata() -> Result<(), Box<dyn Error>> {
+        let expected = fs::read_to_string("./data/TT/postgres_metadata.sql")?;
+        assert_eq!(render_tt(true)?, expected);
+        Ok(())
+    }
+}
@@ -1,115 +1,251 @@
 use std::error::Error;
 use std::fs::File;
-use std::io::BufWriter;
+use std::io::{BufWriter, Write};
 
+use clap::ValueEnum;
 use handlebars::{Context, Handlebars, Helper, Output, RenderContext, RenderError};
 use serde::Serialize;
 
+use crate::bdt_io::{BdtSink, RowFilter};
+use crate::format::date_policy::DatePolicy;
+use crate::format::number_locale::NumberLocale;
+use crate::template::helpers::{data_field_helper, date_helper, oracle_literal_helper, yn_helper};
+use crate::template::Templates;
 use crate::Bdt;
 
-fn data_field_helper(
+mod install;
+mod merge;
+mod plan;
+mod rollback;
+
+pub(crate) use install::dependency_order;
+pub use install::{write_split, INSTALL};
+pub use plan::Plan;
+pub(crate) use rollback::split as rollback_split;
+
+/// Characters of text shown by `prompt`, keeps line within SQL*Plus limit
+const PROMPT_CHARS: usize = 1000;
+
+/// `{{prompt text}}` writes text for SQL*Plus `prompt`, control characters become spaces
+/// and long text is cut
+fn prompt_helper(
     h: &Helper,
     _: &Handlebars,
     _: &Context,
     _: &mut RenderContext,
     out: &mut dyn Output,
 ) -> Result<(), RenderError> {
-    let field_value = h
-        .param(0)
-        .and_then(|v| v.value().as_array())
-        .ok_or(RenderError::new(
-            "Param 0 with field value object is required for data field helper.",
-        ))?;
-    let field_ref = h
-        .param(1)
-        .and_then(|v| v.value().as_str())
-        .ok_or(RenderError::new(
-            "Param 1 with field ref string is required for data field helper.",
-        ))?;
-
-    for value in field_value.iter() {
-        let test_code = value["ref_code"].as_str();
-        if let Some(v) = test_code {
-            if v == field_ref {
-                let print_value = value["value"].as_object();
-                if let Some(pv) = print_value {
-                    if let Some(cdf) = pv.get("Cdf") {
-                        write!(out, "{}", cdf.as_str().get_or_insert(""))?
-                    }
-                    if let Some(text) = pv.get("Text") {
-                        write!(out, "{}", text.as_str().get_or_insert(""))?
-                    }
-                    if let Some(num) = pv.get("Num") {
-                        if let Some(float_val) = num.as_f64() {
-                            write!(out, "{}", float_val)?
-                        }
-                    }
-                    if let Some(date) = pv.get("Date") {
-                        if let Some(date_val) = date.as_str() {
-                            write!(out, "{}", date_val)?
-                        }
-                    }
-                }
-            }
-        }
-    }
+    let text = h.param(0).and_then(|v| v.value().as_str()).unwrap_or("");
+    let line: String = text
+        .chars()
+        .take(PROMPT_CHARS)
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    write!(out, "{}", line)?;
     Ok(())
 }
 
-fn yn_helper(
-    h: &Helper,
-    _: &Handlebars,
-    _: &Context,
-    _: &mut RenderContext,
-    out: &mut dyn Output,
-) -> Result<(), RenderError> {
-    let param = h
-        .param(0)
-        .and_then(|v| v.value().as_bool())
-        .ok_or(RenderError::new(
-            "Param 0 with bool type is required for yn helper.",
-        ))?;
-    if param {
-        write!(out, "Y")?;
-    } else {
-        write!(out, "N")?;
+/// Converts chrono date format to Oracle NLS_DATE_FORMAT
+pub(crate) fn oracle_date_format(format: &str) -> Result<String, Box<dyn Error>> {
+    let mut result = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('d') => result.push_str("DD"),
+            Some('m') => result.push_str("MM"),
+            Some('Y') => result.push_str("YYYY"),
+            Some('y') => result.push_str("YY"),
+            other => {
+                return Err(format!(
+                    "Date format {} is not supported for SQL output, unknown specifier %{}",
+                    format,
+                    other.map(String::from).unwrap_or_default()
+                )
+                .into())
+            }
+        }
     }
-    Ok(())
+    Ok(result)
 }
 
 #[derive(Debug, Serialize)]
 struct BdtList {
+    date_format: String,
+    nls_date_format: String,
+    decimal_separator: char,
+    nls_numeric_characters: &'static str,
+    tables: Vec<Bdt>,
+}
+
+/// How Oracle script brings tables into database
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum SqlMode {
+    /// Delete columns and data of each table and store them again
+    #[default]
+    Load,
+    /// MERGE columns and rows by key, re-running script changes nothing
+    Merge,
+}
+
+/// Oracle script options: output date format, number locale, mode and templates
+#[derive(Clone, Debug, Default)]
+pub struct SqlOptions {
+    /// Accepted date formats and output date format
+    pub dates: DatePolicy,
+    /// Decimal separator of numbers
+    pub numbers: NumberLocale,
+    /// How script brings tables into database
+    pub mode: SqlMode,
+    /// In merge mode, end-date rows missing in bundle instead of deleting them
+    pub end_date_missing: bool,
+    /// Row filter tables were selected with, merge mode deletes or end-dates only missing
+    /// rows matching it. Load mode replaces all rows, deleting filtered out ones.
+    pub rows: RowFilter,
+    /// Templates of script
+    pub templates: Templates,
+}
+
+/// Writes Oracle script of tables to file, in options mode
+pub fn write_bdt(
     tables: Vec<Bdt>,
+    filename: &str,
+    options: &SqlOptions,
+) -> Result<(), Box<dyn Error>> {
+    let file = File::create(filename)?;
+    render_mode(tables, options, BufWriter::new(file))
+}
+
+/// Renders script of tables in options mode
+fn render_mode<W: Write>(
+    tables: Vec<Bdt>,
+    options: &SqlOptions,
+    writer: W,
+) -> Result<(), Box<dyn Error>> {
+    match options.mode {
+        SqlMode::Load => render(tables, options, writer),
+        SqlMode::Merge => merge::render(tables, options, writer),
+    }
+}
+
+/// Writes script undoing deployment of tables, restoring their state in baseline tables
+/// and removing tables missing in baseline
+pub fn write_rollback(
+    tables: Vec<Bdt>,
+    baseline: Vec<Bdt>,
+    filename: &str,
+    options: &SqlOptions,
+) -> Result<(), Box<dyn Error>> {
+    let file = File::create(filename)?;
+    rollback::render(tables, baseline, options, BufWriter::new(file))
 }
 
-pub fn write_bdt(tables: Vec<Bdt>, filename: &str) -> Result<(), Box<dyn Error>> {
+/// Values are written through `sql` helper, so output is not HTML escaped
+fn render<W: Write>(
+    tables: Vec<Bdt>,
+    options: &SqlOptions,
+    writer: W,
+) -> Result<(), Box<dyn Error>> {
+    let SqlOptions {
+        dates,
+        numbers,
+        templates,
+        ..
+    } = options;
     let mut handlebars = Handlebars::new();
+    handlebars.register_escape_fn(handlebars::no_escape);
     handlebars.register_helper("yn", Box::new(yn_helper));
     handlebars.register_helper("df", Box::new(data_field_helper));
-    handlebars
-        .register_template_file("template", "./render/bdtlist.hbs")
-        .unwrap();
-    let file = File::create(filename)?;
-    let mut writer = BufWriter::new(file);
+    handlebars.register_helper("date", Box::new(date_helper));
+    handlebars.register_helper("sql", Box::new(oracle_literal_helper));
+    handlebars.register_helper("prompt", Box::new(prompt_helper));
+    templates.register(&mut handlebars, "bdtlist.hbs")?;
     let bdtlist = BdtList {
-        tables: tables
-            .into_iter()
-            .filter(|bdt| !"skip".eq(bdt.skip.as_str()))
-            .collect(),
+        date_format: dates.output_format.clone(),
+        nls_date_format: oracle_date_format(&dates.output_format)?,
+        decimal_separator: numbers.decimal_separator(),
+        nls_numeric_characters: numbers.nls_numeric_characters(),
+        tables: tables.into_iter().filter_map(Bdt::deployable).collect(),
     };
-    handlebars.render_to_write("template", &bdtlist, &mut writer)?;
+    handlebars.render_to_write("template", &bdtlist, writer)?;
 
     Ok(())
 }
 
+/// Oracle SQL script sink
+pub struct SqlWriter {
+    options: SqlOptions,
+}
+
+impl SqlWriter {
+    /// Sink writing script with options
+    pub fn new(options: SqlOptions) -> Self {
+        SqlWriter { options }
+    }
+}
+
+impl BdtSink for SqlWriter {
+    fn write(&self, tables: Vec<Bdt>, location: &str) -> Result<(), Box<dyn Error>> {
+        write_bdt(tables, location, &self.options)
+    }
+
+    fn write_filtered(
+        &self,
+        tables: Vec<Bdt>,
+        location: &str,
+        rows: &RowFilter,
+    ) -> Result<(), Box<dyn Error>> {
+        let options = SqlOptions {
+            rows: rows.clone(),
+            ..self.options.clone()
+        };
+        write_bdt(tables, location, &options)
+    }
+
+    fn replaces_data(&self) -> bool {
+        self.options.mode == SqlMode::Load
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
-    use crate::bdt::column_value::ColumnValue;
+    use crate::bdt::column_type::ColumnType;
+    use crate::bdt::column_value::ColumnValueType;
+    use crate::bdt::table_name::{Name, NameList};
+    use crate::bdt::Skip;
+    use crate::json_adapter::JsonAdapter;
+    use crate::template::helpers::oracle_literal;
 
     use super::*;
 
+    /// TT tables with all skip flags cleared, so that every value gets rendered
+    fn tt_tables() -> Vec<Bdt> {
+        let mut tables = JsonAdapter::read_bdt("./data/TT/TT.json").unwrap();
+        for bdt in tables.iter_mut() {
+            bdt.skip = Skip::Deploy;
+            for column in bdt.columns.iter_mut() {
+                column.skip = Skip::Deploy;
+            }
+        }
+        tables
+    }
+
+    fn render_script(tables: Vec<Bdt>) -> String {
+        let options = SqlOptions {
+            dates: DatePolicy::new(vec![], "%d.%m.%Y"),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        render(tables, &options, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[allow(mismatched_lifetime_syntaxes)]
     fn setup(source: &str) -> Handlebars {
         let mut handlebars = Handlebars::new();
         handlebars
@@ -137,111 +273,103 @@ mod tests {
     }
 
     #[test]
-    fn yn_helper_test() {
-        let source = "{{yn is_key ~}}";
-        let mut handlebars = setup(source);
-        handlebars.register_helper("yn", Box::new(yn_helper));
-
-        let mut is_key_data: HashMap<&str, bool> = HashMap::new();
-        is_key_data.insert("is_key", true);
-        assert_eq!(handlebars.render("testing", &is_key_data).unwrap(), "Y");
-        let mut no_key_data: HashMap<&str, bool> = HashMap::new();
-        no_key_data.insert("is_key", false);
-        assert_eq!(handlebars.render("testing", &no_key_data).unwrap(), "N");
-    }
+    fn hostile_values_stay_in_literals() {
+        let plain = render_script(tt_tables());
+        let hostile = format!(
+            "Driver's \"choice\" & co\n/\nselect 1 from dual;\r\n{}",
+            "x".repeat(2500)
+        );
+        let mut tables = tt_tables();
+        for bdt in tables.iter_mut() {
+            let names = bdt
+                .names
+                .names()
+                .iter()
+                .map(|name| {
+                    let text = hostile.clone();
+                    Name::new(name.lang.clone(), text.clone(), text.clone(), text)
+                })
+                .collect();
+            bdt.names = NameList::new(names);
+            for column in bdt.columns.iter_mut() {
+                column.title = hostile.clone();
+                column.options = hostile.clone();
+            }
+            for row in bdt.data.iter_mut() {
+                for value in row.values.iter_mut() {
+                    if let ColumnValueType::Text(text) = &mut value.value {
+                        *text = hostile.clone();
+                    }
+                }
+            }
+        }
+        let script = render_script(tables);
 
-    #[test]
-    fn data_field_helper_test_cdf() {
-        let source = r#"{{df data "CDF2_ID" ~}}"#;
-        let data_json = r#"[{
-                "name": "AGE_FROM",
-                "ref_code": "NUM1",
-                "value": {"Num": 0.0}
-            },{
-                "name": "AGE_TILL",
-                "ref_code": "NUM2",
-                "value": {"Num": 4.0}
-            },{
-                "name": "READ_ONLY",
-                "ref_code": "CDF1_ID",
-                "value": {"Cdf": "Y"}
-            },{
-                "name": "DEFAULT_FIELD_VALUE",
-                "ref_code": "CDF2_ID",
-                "value": {"Cdf": "Y"}
-            }]"#;
-        let values: Vec<ColumnValue> = serde_json::from_str(data_json).unwrap();
-
-        let mut handlebars = setup(source);
-        handlebars.register_helper("df", Box::new(data_field_helper));
-
-        let mut column_data: HashMap<&str, Vec<ColumnValue>> = HashMap::new();
-        column_data.insert("data", values);
-        assert_eq!(handlebars.render("testing", &column_data).unwrap(), "Y");
+        let terminators = |script: &str| script.lines().filter(|l| l.starts_with('/')).count();
+        assert_eq!(terminators(&script), terminators(&plain));
+        // SQL*Plus rejects longer lines
+        for line in script.lines() {
+            assert!(line.chars().count() <= 2499, "{}", line);
+        }
+        for line in script.lines().filter(|line| !line.starts_with("prompt ")) {
+            assert!(
+                !line.contains('&') && !line.contains("Driver's"),
+                "{}",
+                line
+            );
+        }
+        let literal = oracle_literal(&hostile);
+        assert!(literal.contains("\n||"));
+        assert!(script.contains(&format!("\n        {},\n", literal)));
+        assert!(script.contains(&format!("\n    ,{}\n", literal)));
     }
 
     #[test]
-    fn data_field_helper_test_num() {
-        let source = r#"{{df data "NUM2" ~}}"#;
-        let data_json = r#"[{
-                "name": "AGE_FROM",
-                "ref_code": "NUM1",
-                "value": {"Num": 0.0}
-            },{
-                "name": "AGE_TILL",
-                "ref_code": "NUM2",
-                "value": {"Num": 4.0}
-            },{
-                "name": "READ_ONLY",
-                "ref_code": "CDF1_ID",
-                "value": {"Cdf": "Y"}
-            },{
-                "name": "DEFAULT_FIELD_VALUE",
-                "ref_code": "CDF2_ID",
-                "value": {"Cdf": "Y"}
-            }]"#;
-        let values: Vec<ColumnValue> = serde_json::from_str(data_json).unwrap();
-
-        let mut handlebars = setup(source);
-        handlebars.register_helper("df", Box::new(data_field_helper));
-
-        let mut column_data: HashMap<&str, Vec<ColumnValue>> = HashMap::new();
-        column_data.insert("data", values);
-        assert_eq!(handlebars.render("testing", &column_data).unwrap(), "4");
+    fn long_literals_stay_within_line_limit() {
+        let mut tables = tt_tables();
+        for bdt in tables.iter_mut() {
+            let names = bdt
+                .names
+                .names()
+                .iter()
+                .map(|name| {
+                    let text = "n".repeat(1000);
+                    Name::new(name.lang.clone(), text.clone(), text.clone(), text)
+                })
+                .collect();
+            bdt.names = NameList::new(names);
+            for (index, column) in bdt.columns.iter_mut().enumerate() {
+                column.title = if index % 2 == 0 {
+                    "t".repeat(1000)
+                } else {
+                    "'".repeat(999)
+                };
+                column.options = "o".repeat(1000);
+                if let ColumnType::Cdf { select_params, .. } = &mut column.col_type {
+                    *select_params = "s".repeat(1000);
+                }
+            }
+        }
+        for mode in [SqlMode::Load, SqlMode::Merge] {
+            let options = SqlOptions {
+                dates: DatePolicy::new(vec![], "%d.%m.%Y"),
+                mode,
+                ..Default::default()
+            };
+            let mut out = Vec::new();
+            render_mode(tables.clone(), &options, &mut out).unwrap();
+            let script = String::from_utf8(out).unwrap();
+            assert!(script.contains(&"'".repeat(1998)));
+            for line in script.lines() {
+                assert!(line.chars().count() <= 2499, "{:?}: {}", mode, line);
+            }
+        }
     }
 
     #[test]
-    fn data_field_helper_test_date() {
-        let source = r#"{{df data "VALID_TO" ~}}"#;
-        let data_json = r#"[{
-                "name": "AGE_FROM",
-                "ref_code": "NUM1",
-                "value": {"Num": 0.0}
-            },{
-                "name": "VALID_TO",
-                "ref_code": "VALID_TO",
-                "value": {
-                    "Date": "01.09.2017"
-                }
-            },{
-                "name": "READ_ONLY",
-                "ref_code": "CDF1_ID",
-                "value": {"Cdf": "Y"}
-            },{
-                "name": "DEFAULT_FIELD_VALUE",
-                "ref_code": "CDF2_ID",
-                "value": {"Cdf": "Y"}
-            }]"#;
-        let values: Vec<ColumnValue> = serde_json::from_str(data_json).unwrap();
-
-        let mut handlebars = setup(source);
-        handlebars.register_helper("df", Box::new(data_field_helper));
-
-        let mut column_data: HashMap<&str, Vec<ColumnValue>> = HashMap::new();
-        column_data.insert("data", values);
-        assert_eq!(
-            handlebars.render("testing", &column_data).unwrap(),
-            "01.09.2017"
-        );
+    fn oracle_date_format_test() {
+        assert_eq!(oracle_date_format("%d.%m.%Y").unwrap(), "DD.MM.YYYY");
+        assert_eq!(oracle_date_format("%Y-%m-%d").unwrap(), "YYYY-MM-DD");
+        assert!(oracle_date_format("%b %d").is_err());
     }
 }
@@ -0,0 +1,192 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use handlebars::Handlebars;
+use serde::Serialize;
+
+use crate::bdt::column_type::ColumnType;
+use crate::bdt::Bdt;
+
+use super::{prompt_helper, SqlOptions};
+
+/// Name of script running per-table scripts
+pub const INSTALL: &str = "install.sql";
+
+#[derive(Debug, Serialize)]
+struct Script {
+    number: usize,
+    ic: String,
+    file: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Install {
+    count: usize,
+    scripts: Vec<Script>,
+}
+
+/// Tables of bundle whose IC is used as codificator by columns of `bdt`
+fn dependencies<'a>(bdt: &Bdt, tables: &'a [Bdt]) -> Vec<&'a str> {
+    tables
+        .iter()
+        .map(|table| table.ic.as_str())
+        .filter(|ic| *ic != bdt.ic)
+        .filter(|ic| {
+            bdt.columns.iter().any(|column| {
+                matches!(&column.col_type, ColumnType::Cdf { codificator_id, .. } if codificator_id == ic)
+            })
+        })
+        .collect()
+}
+
+/// Orders tables after tables they depend on, keeping bundle order otherwise
+pub(crate) fn dependency_order(mut tables: Vec<Bdt>) -> Result<Vec<Bdt>, Box<dyn Error>> {
+    let mut ordered: Vec<Bdt> = Vec::with_capacity(tables.len());
+    while !tables.is_empty() {
+        let ready = tables.iter().position(|bdt| {
+            dependencies(bdt, &tables)
+                .iter()
+                .all(|ic| ordered.iter().any(|done| done.ic == *ic))
+        });
+        match ready {
+            Some(index) => ordered.push(tables.remove(index)),
+            None => {
+                let ics: Vec<&str> = tables.iter().map(|bdt| bdt.ic.as_str()).collect();
+                return Err(format!("tables depend on each other: {}", ics.join(", ")).into());
+            }
+        }
+    }
+    Ok(ordered)
+}
+
+/// Script file name of table, like `01_TT_CONFIG.sql`
+fn file_name(number: usize, width: usize, ic: &str) -> String {
+    let ic: String = ic
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{:0width$}_{}.sql", number, ic, width = width)
+}
+
+/// Writes one script per deployable table in dependency order and `install.sql` running
+/// them with SQL*Plus `@@`, stopping at the first error. Returns written files.
+pub fn write_split(
+    tables: Vec<Bdt>,
+    dir: &Path,
+    options: &SqlOptions,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let tables = dependency_order(tables.into_iter().filter_map(Bdt::deployable).collect())?;
+    fs::create_dir_all(dir)?;
+    let width = tables.len().to_string().len().max(2);
+    let mut written = Vec::new();
+    let mut scripts = Vec::new();
+    for (index, bdt) in tables.into_iter().enumerate() {
+        let file = file_name(index + 1, width, &bdt.ic);
+        let path = dir.join(&file);
+        let ic = bdt.ic.clone();
+        let mut writer = BufWriter::new(File::create(&path)?);
+        super::render_mode(vec![bdt], options, &mut writer)?;
+        writer.flush()?;
+        written.push(path);
+        scripts.push(Script {
+            number: index + 1,
+            ic,
+            file,
+        });
+    }
+
+    let mut handlebars = Handlebars::new();
+    handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars.register_helper("prompt", Box::new(prompt_helper));
+    // --template replaces table scripts only, install script comes from template directory
+    let templates = crate::template::Templates {
+        file: None,
+        dir: options.templates.dir.clone(),
+    };
+    templates.register(&mut handlebars, "install.hbs")?;
+    let install = Install {
+        count: scripts.len(),
+        scripts,
+    };
+    let path = dir.join(INSTALL);
+    let mut writer = BufWriter::new(File::create(&path)?);
+    handlebars.render_to_write("template", &install, &mut writer)?;
+    writer.flush()?;
+    written.push(path);
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bdt::Skip;
+    use crate::json_adapter::JsonAdapter;
+
+    use super::*;
+
+    #[test]
+    fn tables_follow_their_dependencies() -> Result<(), Box<dyn Error>> {
+        let mut tables = JsonAdapter::read_bdt("./data/TT/TT.json")?;
+        let first = tables[1].ic.clone();
+        tables[1].columns[1].col_type = ColumnType::Cdf {
+            codificator_id: "TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE".to_string(),
+            select_params: String::new(),
+        };
+        let ordered: Vec<String> = dependency_order(tables)?
+            .into_iter()
+            .map(|bdt| bdt.ic)
+            .collect();
+        let position = |ic: &str| ordered.iter().position(|done| done == ic).unwrap();
+        assert!(position("TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE") < position(&first));
+        assert_eq!(ordered[0], "TT_CONFIG");
+
+        let mut tables = JsonAdapter::read_bdt("./data/TT/TT.json")?;
+        tables[4].columns[0].col_type = ColumnType::Cdf {
+            codificator_id: tables[1].ic.clone(),
+            select_params: String::new(),
+        };
+        tables[1].columns[1].col_type = ColumnType::Cdf {
+            codificator_id: tables[4].ic.clone(),
+            select_params: String::new(),
+        };
+        assert!(dependency_order(tables).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn split_scripts_and_install() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!("bdt_split_{}", std::process::id()));
+        let mut tables = JsonAdapter::read_bdt("./data/TT/TT.json")?;
+        tables[0].skip = Skip::Deploy;
+        let options = SqlOptions {
+            dates: crate::format::date_policy::DatePolicy::new(vec![], "%d.%m.%Y"),
+            ..Default::default()
+        };
+        let written = write_split(tables, &dir, &options)?;
+        assert_eq!(written.len(), 6);
+        assert_eq!(written[0], dir.join("01_TT_CONFIG.sql"));
+
+        let script = fs::read_to_string(&written[1])?;
+        assert!(script.starts_with("ALTER SESSION SET NLS_NUMERIC_CHARACTERS"));
+        assert!(script.contains("'TT01_POWER_FACTOR_DEFAULT_SEARCH_CONFIG'"));
+        assert!(!script.contains("'TT_CONFIG'"));
+
+        let install = fs::read_to_string(dir.join(INSTALL))?;
+        fs::remove_dir_all(&dir)?;
+        assert!(install.starts_with("whenever sqlerror exit failure rollback\n"));
+        assert!(install.contains(
+            "prompt [2/5] TT01_POWER_FACTOR_DEFAULT_SEARCH_CONFIG: \
+             02_TT01_POWER_FACTOR_DEFAULT_SEARCH_CONFIG.sql\n\
+             @@02_TT01_POWER_FACTOR_DEFAULT_SEARCH_CONFIG.sql\ncommit;\n"
+        ));
+        assert!(install.ends_with("prompt 5 table script(s) installed\n"));
+        Ok(())
+    }
+}
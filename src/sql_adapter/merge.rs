@@ -0,0 +1,499 @@
+use std::error::Error;
+use std::io::Write;
+
+use handlebars::Handlebars;
+use serde::Serialize;
+
+use crate::bdt::column_type::ColumnType;
+use crate::bdt::column_value::ColumnValueType;
+use crate::bdt::{Bdt, Column, RowValues, Skip};
+use crate::bdt_io::RowFilter;
+use crate::template::helpers::{date_helper, oracle_literal, oracle_literal_helper};
+
+use super::{oracle_date_format, prompt_helper, SqlOptions};
+
+const NULL: &str = "NULL";
+
+#[derive(Debug, Serialize)]
+struct MergeTable {
+    #[serde(flatten)]
+    bdt: Bdt,
+    column_merges: Vec<String>,
+    /// Statement deleting columns missing in bundle
+    missing_columns: String,
+    row_merges: Vec<String>,
+    /// Statement deleting or end-dating rows missing in bundle
+    missing: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct MergeList {
+    date_format: String,
+    nls_date_format: String,
+    nls_numeric_characters: &'static str,
+    tables: Vec<MergeTable>,
+}
+
+/// Column of `agc_ctb_data`, named by reference code like `CDF1_ID` or `NUM2`
+fn data_column(column: &Column) -> Result<&str, Box<dyn Error>> {
+    let name = column.ref_code.as_str();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("column reference code '{}' is not a data column", name).into());
+    }
+    Ok(name)
+}
+
+fn table_type(bdt: &Bdt) -> String {
+    format!(
+        "f.get_cdf_id('AGC_CUSTOM_TABLE_TYPE',{})",
+        oracle_literal(&bdt.ic)
+    )
+}
+
+/// `DECODE` compares NULLs as equal, unlike `=`
+fn same(left: &str, right: &str, column: &str) -> String {
+    format!("DECODE({0}.{2}, {1}.{2}, 1, 0) = 1", left, right, column)
+}
+
+fn changed(left: &str, right: &str, column: &str) -> String {
+    format!("DECODE({0}.{2}, {1}.{2}, 0, 1) = 1", left, right, column)
+}
+
+/// SQL expression of row value, NLS independent
+fn value_expression(column: &Column, row: &RowValues) -> String {
+    let value = row
+        .values
+        .iter()
+        .find(|value| value.ref_code == column.ref_code)
+        .map(|value| &value.value);
+    match value {
+        Some(ColumnValueType::Date(Some(date))) => format!("DATE '{}'", date.format("%Y-%m-%d")),
+        Some(ColumnValueType::Num(Some(num))) => num.to_string(),
+        Some(ColumnValueType::Text(text)) if !text.is_empty() => oracle_literal(text),
+        Some(ColumnValueType::Cdf(code)) if !code.is_empty() => match &column.col_type {
+            ColumnType::Cdf { codificator_id, .. } => format!(
+                "f.get_cdf_id({},{})",
+                oracle_literal(codificator_id),
+                oracle_literal(code)
+            ),
+            _ => oracle_literal(code),
+        },
+        _ => NULL.to_string(),
+    }
+}
+
+/// `SELECT <expression> <name>, ... FROM dual` with a value per line, so long literals
+/// stay within SQL*Plus line limit
+fn select(values: &[(String, &str)]) -> String {
+    let values: Vec<String> = values
+        .iter()
+        .map(|(expression, name)| format!("{} {}", expression, name))
+        .collect();
+    format!("SELECT {}\n    FROM dual", values.join(",\n    "))
+}
+
+/// MERGE statement of target aliased `t` and one row source aliased `s`, updating
+/// non-key columns only when they differ
+fn merge(
+    target: &str,
+    source: &str,
+    on: &[String],
+    updates: &[&str],
+    inserts: &[(&str, &str)],
+) -> String {
+    let mut statement = format!(
+        "MERGE INTO {} t\nUSING ({}) s\nON ({})",
+        target,
+        source,
+        on.join(" AND ")
+    );
+    if !updates.is_empty() {
+        let set: Vec<String> = updates
+            .iter()
+            .map(|column| format!("t.{0} = s.{0}", column))
+            .collect();
+        let differ: Vec<String> = updates
+            .iter()
+            .map(|column| changed("t", "s", column))
+            .collect();
+        statement.push_str(&format!(
+            "\nWHEN MATCHED THEN UPDATE SET {}\n    WHERE {}",
+            set.join(", "),
+            differ.join(" OR ")
+        ));
+    }
+    let (columns, values): (Vec<&str>, Vec<&str>) = inserts.iter().copied().unzip();
+    statement.push_str(&format!(
+        "\nWHEN NOT MATCHED THEN INSERT ({})\n    VALUES ({});",
+        columns.join(", "),
+        values.join(", ")
+    ));
+    statement
+}
+
+fn column_merge(bdt: &Bdt, column: &Column) -> String {
+    let codificator = match &column.col_type {
+        ColumnType::Cdf { codificator_id, .. } => {
+            format!("f.get_cdf_id('COD',{})", oracle_literal(codificator_id))
+        }
+        _ => NULL.to_string(),
+    };
+    let select_params = match &column.col_type {
+        ColumnType::Cdf { select_params, .. } => oracle_literal(select_params),
+        _ => "''".to_string(),
+    };
+    let source = select(&[
+        (table_type(bdt), "table_type_id"),
+        (oracle_literal(&column.name), "col_name"),
+        (oracle_literal(&column.title), "title"),
+        (oracle_literal(&column.ref_code), "ref_code"),
+        (
+            column
+                .sequence
+                .map(|sequence| sequence.to_string())
+                .unwrap_or(NULL.to_string()),
+            "sequence",
+        ),
+        (
+            format!("'{}'", if column.is_key { "Y" } else { "N" }),
+            "is_key",
+        ),
+        (codificator, "adm_codificator_id"),
+        (oracle_literal(&column.options), "options"),
+        (select_params, "select_params"),
+    ]);
+    merge(
+        "agc_ctb_columns",
+        &source,
+        &[
+            "t.table_type_id = s.table_type_id".to_string(),
+            "t.col_name = s.col_name".to_string(),
+        ],
+        &[
+            "title",
+            "ref_code",
+            "sequence",
+            "is_key",
+            "adm_codificator_id",
+            "options",
+            "select_params",
+        ],
+        &[
+            ("id", "hibernate_sequence.nextval"),
+            ("log_cre_date", "SYSDATE"),
+            ("table_type_id", "s.table_type_id"),
+            ("col_name", "s.col_name"),
+            ("title", "s.title"),
+            ("ref_code", "s.ref_code"),
+            ("sequence", "s.sequence"),
+            ("is_key", "s.is_key"),
+            ("adm_codificator_id", "s.adm_codificator_id"),
+            ("options", "s.options"),
+            ("select_params", "s.select_params"),
+        ],
+    )
+}
+
+/// Deletes columns of table type which are not in bundle, `columns` are names of all bundle
+/// columns including skipped ones, which stay in database
+fn missing_columns(bdt: &Bdt, columns: &[String]) -> String {
+    let mut statement = format!(
+        "DELETE FROM agc_ctb_columns t\nWHERE t.table_type_id = {}",
+        table_type(bdt)
+    );
+    if !columns.is_empty() {
+        let names: Vec<String> = columns.iter().map(|name| oracle_literal(name)).collect();
+        statement.push_str(&format!("\n  AND t.col_name NOT IN ({})", names.join(", ")));
+    }
+    statement.push(';');
+    statement
+}
+
+/// Rows are identified by key columns and VALID_FROM, by all columns if there are none
+pub(super) fn row_keys(bdt: &Bdt) -> Vec<&Column> {
+    let keys: Vec<&Column> = bdt
+        .columns
+        .iter()
+        .filter(|column| column.is_key || column.ref_code == "VALID_FROM")
+        .collect();
+    if keys.is_empty() {
+        bdt.columns.iter().collect()
+    } else {
+        keys
+    }
+}
+
+fn row_merge(bdt: &Bdt, row: &RowValues) -> Result<String, Box<dyn Error>> {
+    let keys = row_keys(bdt);
+    let mut values = vec![(table_type(bdt), "table_type_id")];
+    let mut on = vec!["t.table_type_id = s.table_type_id".to_string()];
+    let mut updates = Vec::new();
+    let mut inserts = vec![
+        ("id", "hibernate_sequence.nextval".to_string()),
+        ("table_type_id", "s.table_type_id".to_string()),
+    ];
+    for column in bdt.columns.iter() {
+        let name = data_column(column)?;
+        values.push((value_expression(column, row), name));
+        if keys.iter().any(|key| key.ref_code == column.ref_code) {
+            on.push(same("t", "s", name));
+        } else {
+            updates.push(name);
+        }
+        inserts.push((name, format!("s.{}", name)));
+    }
+    let inserts: Vec<(&str, &str)> = inserts
+        .iter()
+        .map(|(column, value)| (*column, value.as_str()))
+        .collect();
+    Ok(merge(
+        "agc_ctb_data",
+        &select(&values),
+        &on,
+        &updates,
+        &inserts,
+    ))
+}
+
+/// Deletes or end-dates rows of table type which have no key in bundle, only among rows
+/// matching row filter the bundle was selected with
+fn missing_rows(bdt: &Bdt, end_date: bool, rows: &RowFilter) -> Result<String, Box<dyn Error>> {
+    let keys = row_keys(bdt);
+    let mut condition = format!("t.table_type_id = {}", table_type(bdt));
+    for filter in rows.oracle_conditions(bdt, "t")? {
+        condition.push_str(&format!("\n  AND {}", filter));
+    }
+    if !bdt.data.is_empty() {
+        let mut rows = Vec::new();
+        for row in bdt.data.iter() {
+            let mut values = Vec::new();
+            for column in keys.iter() {
+                values.push((value_expression(column, row), data_column(column)?));
+            }
+            rows.push(select(&values));
+        }
+        let same: Vec<String> = keys
+            .iter()
+            .map(|column| same("t", "k", &column.ref_code))
+            .collect();
+        condition.push_str(&format!(
+            "\n  AND NOT EXISTS (SELECT 1 FROM (\n    {}\n  ) k WHERE {})",
+            rows.join("\n    UNION ALL "),
+            same.join(" AND ")
+        ));
+    }
+    if !end_date {
+        return Ok(format!("DELETE FROM agc_ctb_data t\nWHERE {};", condition));
+    }
+    if !bdt
+        .columns
+        .iter()
+        .any(|column| column.ref_code == "VALID_TO")
+    {
+        return Err(format!(
+            "Table {} has no VALID_TO column to end-date missing rows",
+            bdt.ic
+        )
+        .into());
+    }
+    Ok(format!(
+        "UPDATE agc_ctb_data t SET t.VALID_TO = TRUNC(SYSDATE) - 1\n\
+         WHERE (t.VALID_TO IS NULL OR t.VALID_TO >= TRUNC(SYSDATE))\n  AND {};",
+        condition
+    ))
+}
+
+impl MergeTable {
+    /// Merge of deployable table, `columns` are names of all its bundle columns
+    fn new(
+        bdt: Bdt,
+        columns: &[String],
+        options: &SqlOptions,
+    ) -> Result<MergeTable, Box<dyn Error>> {
+        let column_merges = bdt
+            .columns
+            .iter()
+            .map(|column| column_merge(&bdt, column))
+            .collect();
+        let missing_columns = missing_columns(&bdt, columns);
+        let (row_merges, missing) = if bdt.skip == Skip::Data {
+            (Vec::new(), None)
+        } else {
+            let rows = bdt
+                .data
+                .iter()
+                .map(|row| row_merge(&bdt, row))
+                .collect::<Result<Vec<String>, Box<dyn Error>>>()?;
+            (
+                rows,
+                Some(missing_rows(&bdt, options.end_date_missing, &options.rows)?),
+            )
+        };
+        Ok(MergeTable {
+            bdt,
+            column_merges,
+            missing_columns,
+            row_merges,
+            missing,
+        })
+    }
+}
+
+/// Script merging tables into place, re-running it changes nothing
+pub(super) fn render<W: Write>(
+    tables: Vec<Bdt>,
+    options: &SqlOptions,
+    writer: W,
+) -> Result<(), Box<dyn Error>> {
+    let mut handlebars = Handlebars::new();
+    handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars.register_helper("date", Box::new(date_helper));
+    handlebars.register_helper("sql", Box::new(oracle_literal_helper));
+    handlebars.register_helper("prompt", Box::new(prompt_helper));
+    options.templates.register(&mut handlebars, "merge.hbs")?;
+    let tables = tables
+        .into_iter()
+        .filter_map(|bdt| {
+            let columns: Vec<String> = bdt.columns.iter().map(|c| c.name.clone()).collect();
+            bdt.deployable()
+                .map(|bdt| MergeTable::new(bdt, &columns, options))
+        })
+        .collect::<Result<Vec<MergeTable>, Box<dyn Error>>>()?;
+    let list = MergeList {
+        date_format: options.dates.output_format.clone(),
+        nls_date_format: oracle_date_format(&options.dates.output_format)?,
+        nls_numeric_characters: options.numbers.nls_numeric_characters(),
+        tables,
+    };
+    handlebars.render_to_write("template", &list, writer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_adapter::JsonAdapter;
+
+    use super::*;
+
+    fn tt_table(ic: &str) -> Bdt {
+        let mut bdt = JsonAdapter::read_bdt("./data/TT/TT.json")
+            .unwrap()
+            .into_iter()
+            .find(|bdt| bdt.ic == ic)
+            .unwrap();
+        bdt.skip = Skip::Deploy;
+        bdt
+    }
+
+    #[test]
+    fn merge_rows_by_keys_and_valid_from() -> Result<(), Box<dyn Error>> {
+        let bdt = tt_table("TT_CONFIG");
+        let statement = row_merge(&bdt, &bdt.data[0])?;
+        assert_eq!(
+            statement,
+            "MERGE INTO agc_ctb_data t\n\
+             USING (SELECT f.get_cdf_id('AGC_CUSTOM_TABLE_TYPE','TT_CONFIG') table_type_id,\n    \
+             NULL VALID_FROM,\n    NULL VALID_TO,\n    \
+             f.get_cdf_id('TT_CONFIG_TYPE_ID','CERTIFICATE_SERIES') CDF1_ID,\n    'AVA' TEXT1,\n    NULL NUM1\n    \
+             FROM dual) s\n\
+             ON (t.table_type_id = s.table_type_id AND DECODE(t.VALID_FROM, s.VALID_FROM, 1, 0) = 1 \
+             AND DECODE(t.CDF1_ID, s.CDF1_ID, 1, 0) = 1 AND DECODE(t.TEXT1, s.TEXT1, 1, 0) = 1 \
+             AND DECODE(t.NUM1, s.NUM1, 1, 0) = 1)\n\
+             WHEN MATCHED THEN UPDATE SET t.VALID_TO = s.VALID_TO\n    \
+             WHERE DECODE(t.VALID_TO, s.VALID_TO, 0, 1) = 1\n\
+             WHEN NOT MATCHED THEN INSERT (id, table_type_id, VALID_FROM, VALID_TO, CDF1_ID, TEXT1, NUM1)\n    \
+             VALUES (hibernate_sequence.nextval, s.table_type_id, s.VALID_FROM, s.VALID_TO, s.CDF1_ID, s.TEXT1, s.NUM1);"
+        );
+        let dated = bdt
+            .data
+            .iter()
+            .find(|row| row.values.iter().any(|v| v.ref_code == "VALID_FROM"))
+            .unwrap();
+        assert!(row_merge(&bdt, dated)?.contains("DATE '2017-09-01' VALID_FROM"));
+        Ok(())
+    }
+
+    #[test]
+    fn rows_without_keys_are_matched_by_all_columns() -> Result<(), Box<dyn Error>> {
+        let bdt = tt_table("TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE");
+        let statement = row_merge(&bdt, &bdt.data[1])?;
+        assert!(statement.contains(
+            "5.0 NUM1,\n    9.0 NUM2,\n    f.get_cdf_id('YN','N') CDF1_ID,\n    NULL CDF2_ID"
+        ));
+        assert!(statement.contains("DECODE(t.CDF2_ID, s.CDF2_ID, 1, 0) = 1)"));
+        assert!(!statement.contains("WHEN MATCHED"));
+        Ok(())
+    }
+
+    #[test]
+    fn delete_or_end_date_missing_rows() -> Result<(), Box<dyn Error>> {
+        let mut bdt = tt_table("TT_CONFIG");
+        let delete = missing_rows(&bdt, false, &RowFilter::default())?;
+        assert!(delete.starts_with("DELETE FROM agc_ctb_data t\nWHERE t.table_type_id = "));
+        assert_eq!(delete.matches("UNION ALL").count(), bdt.data.len() - 1);
+        let end_date = missing_rows(&bdt, true, &RowFilter::default())?;
+        assert!(end_date.starts_with(
+            "UPDATE agc_ctb_data t SET t.VALID_TO = TRUNC(SYSDATE) - 1\n\
+             WHERE (t.VALID_TO IS NULL OR t.VALID_TO >= TRUNC(SYSDATE))\n  AND t.table_type_id = "
+        ));
+        bdt.data.clear();
+        assert!(!missing_rows(&bdt, false, &RowFilter::default())?.contains("NOT EXISTS"));
+
+        let bdt = tt_table("TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE");
+        assert!(missing_rows(&bdt, true, &RowFilter::default()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn missing_rows_limited_to_row_filter() -> Result<(), Box<dyn Error>> {
+        let bdt = tt_table("TT_CONFIG");
+        let rows = RowFilter::new(vec!["VALID_TO is null and CONFIG_TYPE != 'AVA'".to_string()])?;
+        let delete = missing_rows(&bdt, false, &rows)?;
+        assert!(delete.contains(
+            "\n  AND t.VALID_TO IS NULL\n  AND t.CDF1_ID <> f.get_cdf_id('TT_CONFIG_TYPE_ID','AVA')\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn delete_missing_columns() {
+        let bdt = tt_table("TT_CONFIG");
+        let columns: Vec<String> = bdt.columns.iter().map(|c| c.name.clone()).collect();
+        assert_eq!(
+            missing_columns(&bdt, &columns),
+            "DELETE FROM agc_ctb_columns t\n\
+             WHERE t.table_type_id = f.get_cdf_id('AGC_CUSTOM_TABLE_TYPE','TT_CONFIG')\n  \
+             AND t.col_name NOT IN ('VALID_FROM', 'VALID_TO', 'CONFIG_TYPE', 'CONFIG_VALUE', 'CONFIG_NUM_VALUE');"
+        );
+        assert!(!missing_columns(&bdt, &[]).contains("NOT IN"));
+    }
+
+    #[test]
+    fn skipped_columns_are_not_deleted() -> Result<(), Box<dyn Error>> {
+        let mut bdt = tt_table("TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE");
+        bdt.columns[2].skip = Skip::All;
+        let mut out = Vec::new();
+        render(vec![bdt], &SqlOptions::default(), &mut out)?;
+        let script = String::from_utf8(out)?;
+        assert!(script.contains(
+            "AND t.col_name NOT IN ('AGE_FROM', 'AGE_TILL', 'READ_ONLY', 'DEFAULT_FIELD_VALUE');"
+        ));
+        assert!(!script.contains("'READ_ONLY' col_name"));
+        Ok(())
+    }
+
+    #[test]
+    fn skip_data_tables_keep_their_rows() -> Result<(), Box<dyn Error>> {
+        let mut bdt = tt_table("TT_COMBINED_POLICY_DISCOUNT");
+        bdt.skip = Skip::Data;
+        let options = SqlOptions {
+            end_date_missing: true,
+            ..Default::default()
+        };
+        let table = MergeTable::new(bdt.deployable().unwrap(), &[], &options)?;
+        assert_eq!(table.column_merges.len(), 4);
+        assert!(table.row_merges.is_empty());
+        assert!(table.missing.is_none());
+        Ok(())
+    }
+}
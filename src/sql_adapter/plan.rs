@@ -0,0 +1,506 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::bdt::column_value::ColumnValueType;
+use crate::bdt::{Bdt, Column, RowValues, Skip};
+
+use super::merge::row_keys;
+use super::{SqlMode, SqlOptions};
+
+/// Row as comparable text of its non-empty values in `columns` ordered by reference code
+fn row_key(row: &RowValues, columns: &[&str]) -> String {
+    let mut values: Vec<String> = row
+        .values
+        .iter()
+        .filter(|value| columns.contains(&value.ref_code.as_str()))
+        .filter_map(|value| {
+            let text = match &value.value {
+                ColumnValueType::Date(Some(date)) => date.to_string(),
+                ColumnValueType::Num(Some(num)) => num.normalize().to_string(),
+                ColumnValueType::Cdf(text) | ColumnValueType::Text(text) if !text.is_empty() => {
+                    text.clone()
+                }
+                _ => return None,
+            };
+            Some(format!("{}={}", value.ref_code, text))
+        })
+        .collect();
+    values.sort();
+    values.join("\u{1f}")
+}
+
+/// Column definition without database id and skip flag
+fn column_key(column: &Column) -> String {
+    format!(
+        "{}|{}|{}|{:?}|{}|{}",
+        column.title,
+        column.ref_code,
+        serde_json::to_string(&column.col_type).unwrap_or_default(),
+        column.sequence,
+        column.is_key,
+        column.options
+    )
+}
+
+fn names_key(bdt: &Bdt) -> String {
+    serde_json::to_string(&bdt.names).unwrap_or_default()
+}
+
+/// Row changes of bundle against baseline
+#[derive(Debug, Default, PartialEq)]
+struct RowChanges {
+    added: usize,
+    updated: usize,
+    removed: usize,
+}
+
+/// Compares rows by `keys` columns, counting duplicates. Rows with the same key and
+/// other values in any of `columns` are updated.
+fn row_changes(
+    rows: &[RowValues],
+    baseline: &[RowValues],
+    keys: &[&str],
+    columns: &[&str],
+) -> RowChanges {
+    let mut base: HashMap<String, Vec<String>> = HashMap::new();
+    for row in baseline {
+        base.entry(row_key(row, keys))
+            .or_default()
+            .push(row_key(row, columns));
+    }
+    let mut changes = RowChanges::default();
+    let mut changed = Vec::new();
+    for row in rows {
+        let values = row_key(row, columns);
+        match base.get_mut(&row_key(row, keys)) {
+            Some(base) if !base.is_empty() => match base.iter().position(|v| *v == values) {
+                Some(index) => {
+                    base.swap_remove(index);
+                }
+                None => changed.push((row_key(row, keys), values)),
+            },
+            _ => changes.added += 1,
+        }
+    }
+    // rows with changed values take baseline rows left after exact matches
+    for (key, _) in changed {
+        match base.get_mut(&key) {
+            Some(base) if !base.is_empty() => {
+                base.pop();
+                changes.updated += 1;
+            }
+            _ => changes.added += 1,
+        }
+    }
+    changes.removed = base.values().map(Vec::len).sum();
+    changes
+}
+
+/// Net changes of table against its baseline state
+#[derive(Debug, Default, PartialEq)]
+struct Changes {
+    names: bool,
+    added_columns: Vec<String>,
+    removed_columns: Vec<String>,
+    changed_columns: Vec<String>,
+    /// Baseline rows, or `None` when deployment keeps data
+    baseline_rows: Option<usize>,
+    rows: RowChanges,
+}
+
+impl Changes {
+    fn new(bdt: &Bdt, baseline: &Bdt, mode: SqlMode) -> Self {
+        let find = |columns: &[Column], name: &str| {
+            columns.iter().find(|column| column.name == name).cloned()
+        };
+        let mut changes = Changes {
+            names: names_key(bdt) != names_key(baseline),
+            ..Default::default()
+        };
+        for column in bdt.columns.iter() {
+            match find(&baseline.columns, &column.name) {
+                None => changes.added_columns.push(column.name.clone()),
+                Some(base) if column_key(&base) != column_key(column) => {
+                    changes.changed_columns.push(column.name.clone())
+                }
+                _ => {}
+            }
+        }
+        for column in baseline.columns.iter() {
+            if find(&bdt.columns, &column.name).is_none() {
+                changes.removed_columns.push(column.name.clone());
+            }
+        }
+        if bdt.skip != Skip::Data {
+            let columns: Vec<&str> = bdt
+                .columns
+                .iter()
+                .chain(baseline.columns.iter())
+                .map(|column| column.ref_code.as_str())
+                .collect();
+            // load replaces whole rows, merge updates rows matched by keys
+            let keys: Vec<&str> = match mode {
+                SqlMode::Load => columns.clone(),
+                SqlMode::Merge => row_keys(bdt)
+                    .iter()
+                    .map(|column| column.ref_code.as_str())
+                    .collect(),
+            };
+            changes.baseline_rows = Some(baseline.data.len());
+            changes.rows = row_changes(&bdt.data, &baseline.data, &keys, &columns);
+        }
+        changes
+    }
+}
+
+/// What script does to one table
+#[derive(Debug, PartialEq)]
+enum TablePlan {
+    /// Table has `skip`
+    Skipped { ic: String },
+    Deployed {
+        ic: String,
+        name: String,
+        columns: usize,
+        /// Loaded rows, or `None` for `skipData`
+        rows: Option<usize>,
+        /// Net effect when baseline is given, `None` for tables new to baseline
+        baseline: Option<Option<Changes>>,
+    },
+}
+
+/// Per table summary of Oracle script, optionally with net effect against baseline
+#[derive(Debug)]
+pub struct Plan {
+    mode: SqlMode,
+    end_date_missing: bool,
+    tables: Vec<TablePlan>,
+}
+
+impl Plan {
+    /// Merge mode compares only baseline rows matching row filter of options, as it keeps
+    /// other rows
+    pub fn new(
+        tables: Vec<Bdt>,
+        baseline: Option<Vec<Bdt>>,
+        options: &SqlOptions,
+    ) -> Result<Self, Box<dyn Error>> {
+        let baseline = match baseline {
+            None => None,
+            Some(tables) => {
+                let mut deployed = Vec::new();
+                for mut bdt in tables {
+                    bdt.skip = Skip::Deploy;
+                    if let Some(mut bdt) = bdt.deployable() {
+                        if options.mode == SqlMode::Merge {
+                            options.rows.retain(&mut bdt)?;
+                        }
+                        deployed.push(bdt);
+                    }
+                }
+                Some(deployed)
+            }
+        };
+        let tables = tables
+            .into_iter()
+            .map(|bdt| {
+                let ic = bdt.ic.clone();
+                match bdt.deployable() {
+                    None => TablePlan::Skipped { ic },
+                    Some(bdt) => TablePlan::Deployed {
+                        ic,
+                        name: bdt
+                            .names
+                            .names()
+                            .first()
+                            .map(|name| name.name.clone())
+                            .unwrap_or_default(),
+                        columns: bdt.columns.len(),
+                        rows: (bdt.skip != Skip::Data).then_some(bdt.data.len()),
+                        baseline: baseline.as_ref().map(|baseline| {
+                            baseline
+                                .iter()
+                                .find(|base| base.ic == bdt.ic)
+                                .map(|base| Changes::new(&bdt, base, options.mode))
+                        }),
+                    },
+                }
+            })
+            .collect();
+        Ok(Plan {
+            mode: options.mode,
+            end_date_missing: options.end_date_missing,
+            tables,
+        })
+    }
+
+    fn missing(&self) -> &'static str {
+        if self.mode == SqlMode::Merge && self.end_date_missing {
+            "end-dated"
+        } else {
+            "deleted"
+        }
+    }
+
+    fn write_table(&self, f: &mut fmt::Formatter, table: &TablePlan) -> fmt::Result {
+        let (ic, name, columns, rows, baseline) = match table {
+            TablePlan::Skipped { ic } => return writeln!(f, "{}: skipped", ic),
+            TablePlan::Deployed {
+                ic,
+                name,
+                columns,
+                rows,
+                baseline,
+            } => (ic, name, columns, rows, baseline),
+        };
+        let action = match baseline {
+            None => "create or update",
+            Some(None) => "create",
+            Some(Some(_)) => "update",
+        };
+        writeln!(f, "{} ({}): {}", ic, name, action)?;
+        let changes = baseline.as_ref().and_then(Option::as_ref);
+        if let Some(changes) = changes {
+            if changes.names {
+                writeln!(f, "  names: changed")?;
+            }
+        }
+        match self.mode {
+            SqlMode::Load => writeln!(f, "  columns: {} deleted and re-inserted", columns)?,
+            SqlMode::Merge => writeln!(
+                f,
+                "  columns: {} merged, columns missing in bundle deleted",
+                columns
+            )?,
+        }
+        if let Some(changes) = changes {
+            for (label, columns) in [
+                ("added", &changes.added_columns),
+                ("removed", &changes.removed_columns),
+                ("changed", &changes.changed_columns),
+            ] {
+                if !columns.is_empty() {
+                    writeln!(f, "    {}: {}", label, columns.join(", "))?;
+                }
+            }
+        }
+        let rows = match rows {
+            None => return writeln!(f, "  data: kept (skipData)"),
+            Some(rows) => rows,
+        };
+        match (
+            self.mode,
+            baseline,
+            changes.and_then(|changes| changes.baseline_rows),
+        ) {
+            (_, Some(None), _) => writeln!(f, "  data: {} loaded", rows)?,
+            (SqlMode::Load, _, Some(baseline)) => {
+                writeln!(f, "  data: {} rows deleted, {} loaded", baseline, rows)?
+            }
+            (SqlMode::Load, _, None) => writeln!(f, "  data: all rows deleted, {} loaded", rows)?,
+            (SqlMode::Merge, _, _) => writeln!(
+                f,
+                "  data: {} rows merged, rows missing in bundle {}",
+                rows,
+                self.missing()
+            )?,
+        }
+        if let Some(changes) = changes {
+            let RowChanges {
+                added,
+                updated,
+                removed,
+            } = changes.rows;
+            writeln!(
+                f,
+                "    net: {} added, {} updated, {} {}, {} unchanged",
+                added,
+                updated,
+                removed,
+                self.missing(),
+                rows - added - updated
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut deployed = 0;
+        let mut created = 0;
+        let mut loaded = 0;
+        for table in self.tables.iter() {
+            self.write_table(f, table)?;
+            if let TablePlan::Deployed { rows, baseline, .. } = table {
+                deployed += 1;
+                created += usize::from(matches!(baseline, Some(None)));
+                loaded += rows.unwrap_or_default();
+            }
+        }
+        write!(
+            f,
+            "{} table(s) deployed, {} skipped, {} row(s) in script",
+            deployed,
+            self.tables.len() - deployed,
+            loaded
+        )?;
+        if self.tables.iter().any(|table| {
+            matches!(
+                table,
+                TablePlan::Deployed {
+                    baseline: Some(_),
+                    ..
+                }
+            )
+        }) {
+            write!(f, ", {} table(s) new to baseline", created)?;
+        }
+        writeln!(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bdt::table_name::Name;
+    use crate::bdt_io::RowFilter;
+    use crate::json_adapter::JsonAdapter;
+
+    use super::*;
+
+    const TT02: &str = "TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE";
+
+    fn tt_tables() -> Vec<Bdt> {
+        JsonAdapter::read_bdt("./data/TT/TT.json").unwrap()
+    }
+
+    #[test]
+    fn plan_without_baseline() {
+        let plan = Plan::new(tt_tables(), None, &SqlOptions::default())
+            .unwrap()
+            .to_string();
+        assert!(plan.contains(
+            "TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE (TT02 Depreciation config by vehicle age): \
+             create or update\n  columns: 4 deleted and re-inserted\n  \
+             data: all rows deleted, 3 loaded\n"
+        ));
+        assert!(plan.contains("TT_CONFIG: skipped\n"));
+        assert!(plan.contains("  data: kept (skipData)\n"));
+        assert!(!plan.contains("net:"));
+        assert!(plan.ends_with("4 table(s) deployed, 1 skipped, 12 row(s) in script\n"));
+    }
+
+    #[test]
+    fn net_effect_against_baseline() {
+        let mut baseline = tt_tables();
+        baseline.retain(|bdt| bdt.ic == TT02);
+        let base = &mut baseline[0];
+        base.data.remove(0);
+        base.columns[0].title = "Changed".to_string();
+        base.columns.pop();
+        base.names.push(Name::default());
+        let mut tables = tt_tables();
+        tables.retain(|bdt| bdt.ic == TT02 || bdt.ic == "TT_CONFIG" || bdt.ic.starts_with("TT01"));
+        let bdt = tables.iter_mut().find(|bdt| bdt.ic == TT02).unwrap();
+        bdt.data.pop();
+
+        let options = SqlOptions {
+            mode: SqlMode::Merge,
+            end_date_missing: true,
+            ..Default::default()
+        };
+        let plan = Plan::new(tables, Some(baseline), &options)
+            .unwrap()
+            .to_string();
+        assert!(plan.contains(
+            ": create\n  columns: 6 merged, columns missing in bundle deleted\n  data: 9 loaded\n"
+        ));
+        assert!(plan.contains(
+            "TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE (TT02 Depreciation config by vehicle age): \
+             update\n  names: changed\n  columns: 4 merged, columns missing in bundle deleted\n    \
+             added: DEFAULT_FIELD_VALUE\n    changed: AGE_FROM\n  \
+             data: 2 rows merged, rows missing in bundle end-dated\n    \
+             net: 1 added, 0 updated, 1 end-dated, 1 unchanged\n"
+        ));
+        assert!(plan.ends_with(
+            "2 table(s) deployed, 1 skipped, 11 row(s) in script, 1 table(s) new to baseline\n"
+        ));
+    }
+
+    #[test]
+    fn merge_matches_rows_by_keys() {
+        let tt01 = |bdt: &Bdt| bdt.ic.starts_with("TT01");
+        let mut baseline = tt_tables();
+        baseline.retain(tt01);
+        baseline[0].data.remove(1);
+        let sequence = baseline[0].data[0]
+            .values
+            .iter_mut()
+            .find(|value| value.ref_code == "NUM1")
+            .unwrap();
+        sequence.value = ColumnValueType::Num(Some(99.into()));
+        let mut tables = tt_tables();
+        tables.retain(tt01);
+
+        let net = |mode: SqlMode| {
+            let options = SqlOptions {
+                mode,
+                ..Default::default()
+            };
+            let plan = Plan::new(tables.clone(), Some(baseline.clone()), &options)
+                .unwrap()
+                .to_string();
+            plan.lines()
+                .find_map(|line| line.trim().strip_prefix("net: "))
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            net(SqlMode::Merge),
+            "1 added, 1 updated, 0 deleted, 7 unchanged"
+        );
+        assert_eq!(
+            net(SqlMode::Load),
+            "2 added, 0 updated, 1 deleted, 7 unchanged"
+        );
+    }
+
+    #[test]
+    fn merge_keeps_rows_left_out_by_row_filter() {
+        let config = |bdt: &Bdt| bdt.ic == "TT_CONFIG";
+        let mut baseline = tt_tables();
+        baseline.retain(config);
+        baseline[0].skip = Skip::Deploy;
+        for column in baseline[0].columns.iter_mut() {
+            column.skip = Skip::Deploy;
+        }
+        let rows = RowFilter::new(vec!["VALID_TO is null".to_string()]).unwrap();
+        let tables = rows.apply(baseline.clone()).unwrap();
+        let kept = tables[0].data.len();
+        assert!(kept < baseline[0].data.len());
+
+        let net = |mode: SqlMode| {
+            let options = SqlOptions {
+                mode,
+                rows: rows.clone(),
+                ..Default::default()
+            };
+            let plan = Plan::new(tables.clone(), Some(baseline.clone()), &options)
+                .unwrap()
+                .to_string();
+            plan.lines()
+                .find_map(|line| line.trim().strip_prefix("net: "))
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            net(SqlMode::Merge),
+            format!("0 added, 0 updated, 0 deleted, {} unchanged", kept)
+        );
+        assert_eq!(
+            net(SqlMode::Load),
+            format!("0 added, 0 updated, 1 deleted, {} unchanged", kept)
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::error::Error;
+use std::io::Write;
+
+use handlebars::Handlebars;
+use serde::Serialize;
+
+use crate::bdt::{Bdt, Skip};
+use crate::template::helpers::{date_helper, oracle_literal_helper};
+use crate::template::Templates;
+
+use super::{prompt_helper, SqlMode, SqlOptions};
+
+#[derive(Debug, Serialize)]
+struct RemovalList {
+    date_format: String,
+    tables: Vec<Bdt>,
+}
+
+/// Splits deployed tables into baseline tables to restore and new tables to remove.
+/// Restored tables keep baseline data untouched when deployment skips data.
+pub(crate) fn split(tables: Vec<Bdt>, mut baseline: Vec<Bdt>) -> (Vec<Bdt>, Vec<Bdt>) {
+    let mut restore = Vec::new();
+    let mut remove = Vec::new();
+    for bdt in tables.into_iter().filter_map(Bdt::deployable) {
+        match baseline.iter().position(|base| base.ic == bdt.ic) {
+            Some(index) => {
+                let mut base = baseline.swap_remove(index);
+                base.skip = match bdt.skip {
+                    Skip::Data => Skip::Data,
+                    _ => Skip::Deploy,
+                };
+                restore.extend(base.deployable());
+            }
+            None => remove.push(bdt),
+        }
+    }
+    (restore, remove)
+}
+
+/// Renders script undoing deployment of tables: tables found in baseline get their
+/// baseline state back with load script, tables missing in it are removed
+pub(super) fn render<W: Write>(
+    tables: Vec<Bdt>,
+    baseline: Vec<Bdt>,
+    options: &SqlOptions,
+    mut writer: W,
+) -> Result<(), Box<dyn Error>> {
+    let (restore, remove) = split(tables, baseline);
+    let load = SqlOptions {
+        mode: SqlMode::Load,
+        end_date_missing: false,
+        ..options.clone()
+    };
+    super::render(restore, &load, &mut writer)?;
+
+    let mut handlebars = Handlebars::new();
+    handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars.register_helper("date", Box::new(date_helper));
+    handlebars.register_helper("sql", Box::new(oracle_literal_helper));
+    handlebars.register_helper("prompt", Box::new(prompt_helper));
+    // --template replaces deployment script only, removals come from template directory
+    let templates = Templates {
+        file: None,
+        dir: options.templates.dir.clone(),
+    };
+    templates.register(&mut handlebars, "rollback.hbs")?;
+    let list = RemovalList {
+        date_format: options.dates.output_format.clone(),
+        tables: remove,
+    };
+    handlebars.render_to_write("template", &list, writer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::date_policy::DatePolicy;
+    use crate::json_adapter::JsonAdapter;
+
+    use super::*;
+
+    fn tt_tables() -> Vec<Bdt> {
+        let mut tables = JsonAdapter::read_bdt("./data/TT/TT.json").unwrap();
+        for bdt in tables.iter_mut() {
+            bdt.skip = Skip::Deploy;
+        }
+        tables
+    }
+
+    #[test]
+    fn restore_baseline_and_remove_new_tables() -> Result<(), Box<dyn Error>> {
+        let mut baseline = tt_tables();
+        baseline.retain(|bdt| bdt.ic == "TT_CONFIG" || bdt.ic == "TT_COMBINED_POLICY_DISCOUNT");
+        for bdt in baseline.iter_mut() {
+            bdt.data.truncate(1);
+        }
+        let mut tables = tt_tables();
+        tables.retain(|bdt| bdt.ic == "TT_CONFIG" || bdt.ic.starts_with("TT01"));
+        let new: Vec<String> = tables
+            .iter()
+            .filter(|bdt| bdt.ic != "TT_CONFIG")
+            .map(|bdt| bdt.ic.clone())
+            .collect();
+
+        let options = SqlOptions {
+            dates: DatePolicy::new(vec![], "%d.%m.%Y"),
+            mode: SqlMode::Merge,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        render(tables, baseline, &options, &mut out)?;
+        let script = String::from_utf8(out)?;
+
+        assert_eq!(script.matches("cfgload.store_ctb_data(").count(), 1);
+        assert!(script.contains("delete from agc_ctb_data cd where cd.table_type_id = f.get_cdf_id('AGC_CUSTOM_TABLE_TYPE','TT_CONFIG');"));
+        assert!(!script.contains("TT_COMBINED_POLICY_DISCOUNT"));
+        assert!(!script.contains("MERGE"));
+        assert!(!new.is_empty());
+        assert_eq!(
+            script
+                .matches("P_VALID_TO=>TO_CHAR(TRUNC(SYSDATE) - 1)")
+                .count(),
+            new.len()
+        );
+        for ic in new {
+            assert!(script.contains(&format!(
+                "delete from agc_custom_table_type ctt where ctt.id = f.get_cdf_id('AGC_CUSTOM_TABLE_TYPE','{}');",
+                ic
+            )));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn skip_data_deployment_keeps_baseline_data() {
+        let baseline = tt_tables();
+        let mut tables = tt_tables();
+        tables.retain(|bdt| bdt.ic == "TT_CONFIG");
+        tables[0].skip = Skip::Data;
+        let (restore, remove) = split(tables, baseline);
+        assert!(remove.is_empty());
+        assert_eq!(restore[0].skip, Skip::Data);
+        assert!(restore[0].data.is_empty());
+    }
+}
@@ -1,103 +1,198 @@
 use handlebars::{Context, Handlebars, Helper, Output, RenderContext, RenderError};
 use serde::Serialize;
-use std::io::BufWriter;
+use serde_json::Value;
+use std::io::{BufWriter, Write};
 use std::{error::Error, fs::File};
 
+use crate::bdt_io::BdtSink;
+use crate::classifier::ClassifierRecord;
+use crate::template::helpers::{
+    array_last_comma, columns_param, format_date, has_key, key_names_helper, some_helper,
+};
+use crate::template::Templates;
 use crate::Bdt;
 
-pub struct SqliteAdapter {}
+/// Default date format, same as of `csv data` files
+pub const FORMAT: &str = "%Y-%m-%d";
+
+/// SQLite load script sink
+pub struct SqliteAdapter {
+    /// Format of written dates
+    pub date_format: String,
+    /// Templates of script
+    pub templates: Templates,
+}
+
+impl Default for SqliteAdapter {
+    fn default() -> Self {
+        SqliteAdapter {
+            date_format: FORMAT.to_string(),
+            templates: Templates::default(),
+        }
+    }
+}
 
 #[derive(Debug, Serialize)]
 struct BdtList {
+    date_format: String,
     tables: Vec<Bdt>,
+    classifiers: Vec<ClassifierRecord>,
 }
 
-// handlebars_helper!(last: |array: , index: usize| if array.len() == index { ",".to_string() } else { "".to_string()});
+fn write_literal(out: &mut dyn Output, value: &Value) -> Result<(), RenderError> {
+    match value {
+        Value::Null => write!(out, "NULL")?,
+        Value::Bool(flag) => write!(out, "'{}'", if *flag { "Y" } else { "N" })?,
+        Value::Number(num) => write!(out, "{}", num)?,
+        Value::String(text) => write!(out, "'{}'", text.replace('\'', "''"))?,
+        _ => write!(out, "'{}'", value.to_string().replace('\'', "''"))?,
+    }
+    Ok(())
+}
 
-pub fn array_last_comma(
+/// Writes param 0 as SQLite literal, missing values become `NULL`
+fn literal_helper(
     h: &Helper,
     _: &Handlebars,
     _: &Context,
     _: &mut RenderContext,
     out: &mut dyn Output,
 ) -> Result<(), RenderError> {
-    let length = h
-        .param(0)
-        .as_ref()
-        .and_then(|v| v.value().as_array())
-        .map(|arr| arr.len())
-        .ok_or(RenderError::new(
-            "Param 0 with 'array' type is required for array_last_comma helper",
-        ))?;
-
-    let index = h
-        .param(1)
-        .as_ref()
-        .and_then(|v| v.value().as_u64())
-        .ok_or(RenderError::new(
-            "Param 1 with 'usize' type is required for array_last_comma helper",
-        ))?;
-
-    if length as u64 != index + 1 {
-        write!(out, ",")?;
-    } else {
-        write!(out, "")?;
+    match h.param(0) {
+        Some(param) if !param.is_value_missing() => write_literal(out, param.value()),
+        _ => write_literal(out, &Value::Null),
     }
+}
 
+/// Writes BDT date string (param 0) as date literal in `date_format` of template data,
+/// ISO by default, same as in CSV data files
+fn date_literal_helper(
+    h: &Helper,
+    _: &Handlebars,
+    ctx: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> Result<(), RenderError> {
+    let date = h.param(0).and_then(|v| v.value().as_str());
+    match date {
+        Some(date_str) => {
+            let format = ctx.data().get("date_format").and_then(|v| v.as_str());
+            let date = format_date(ctx, date_str, Some(format.unwrap_or(FORMAT)))?;
+            write!(out, "'{}'", date)?;
+        }
+        None => write!(out, "NULL")?,
+    }
     Ok(())
 }
 
-fn some_helper(
+fn column_name_by_ref<'a>(columns: &'a [Value], ref_code: &str) -> Option<&'a str> {
+    columns
+        .iter()
+        .find(|column| column["ref_code"].as_str() == Some(ref_code))
+        .and_then(|column| column["name"].as_str())
+}
+
+/// Writes condition selecting rows valid at SQL date expression given in param 1.
+/// Empty VALID_FROM/VALID_TO values (as imported from CSV) are treated as open interval,
+/// dates are compared with `date()`, so ISO dates with or without time match alike.
+fn validity_helper(
     h: &Helper,
     _: &Handlebars,
     _: &Context,
     _: &mut RenderContext,
     out: &mut dyn Output,
 ) -> Result<(), RenderError> {
-    let param = h.param(0);
-    match param {
-        Some(value) => {
-            if value.is_value_missing() {
-                write!(out, "")?;
-            } else {
-                write!(out, "TEXT")?;
-            }
-        }
-        _ => {
-            write!(out, "")?;
-        }
+    let columns = columns_param(h, "validity")?;
+    let as_of = h
+        .param(1)
+        .and_then(|v| v.value().as_str())
+        .ok_or(RenderError::new(
+            "Param 1 with date expression is required for validity helper.",
+        ))?;
+
+    let mut conditions: Vec<String> = vec![];
+    if let Some(valid_from) = column_name_by_ref(columns, "VALID_FROM") {
+        conditions.push(format!(
+            "({0} IS NULL OR {0} = '' OR date({0}) <= date({1}))",
+            valid_from, as_of
+        ));
+    }
+    if let Some(valid_to) = column_name_by_ref(columns, "VALID_TO") {
+        conditions.push(format!(
+            "({0} IS NULL OR {0} = '' OR date({0}) >= date({1}))",
+            valid_to, as_of
+        ));
+    }
+    if conditions.is_empty() {
+        write!(out, "1 = 1")?;
+    } else {
+        write!(out, "{}", conditions.join(" AND "))?;
     }
     Ok(())
 }
 
 impl SqliteAdapter {
-    pub fn write_bdt(tables: Vec<Bdt>, filename: &str) -> Result<(), Box<dyn Error>> {
+    /// Writes SQLite script of tables and classifiers to file
+    pub fn write_bdt(
+        tables: Vec<Bdt>,
+        classifiers: Vec<ClassifierRecord>,
+        filename: &str,
+        date_format: &str,
+        templates: &Templates,
+    ) -> Result<(), Box<dyn Error>> {
+        let file = File::create(filename)?;
+        let mut writer = BufWriter::new(file);
+        SqliteAdapter::render(tables, classifiers, date_format, templates, &mut writer)
+    }
+
+    fn render<W: Write>(
+        tables: Vec<Bdt>,
+        classifiers: Vec<ClassifierRecord>,
+        date_format: &str,
+        templates: &Templates,
+        writer: W,
+    ) -> Result<(), Box<dyn Error>> {
         let mut handlebars = Handlebars::new();
         handlebars.register_helper("some", Box::new(some_helper));
         handlebars.register_helper("last", Box::new(array_last_comma));
-        handlebars
-            .register_template_file("template", "./render/sqlite.hbs")
-            .unwrap();
-        let file = File::create(filename)?;
-        let mut writer = BufWriter::new(file);
+        handlebars.register_helper("q", Box::new(literal_helper));
+        handlebars.register_helper("qdate", Box::new(date_literal_helper));
+        handlebars.register_helper("has_key", Box::new(has_key));
+        handlebars.register_helper("keys", Box::new(key_names_helper));
+        handlebars.register_helper("validity", Box::new(validity_helper));
+        templates.register(&mut handlebars, "sqlite.hbs")?;
         let bdtlist = BdtList {
-            tables: tables
-                .into_iter()
-                .filter(|bdt| !"skip".eq(bdt.skip.as_str()))
-                .collect(),
+            date_format: date_format.to_string(),
+            tables: tables.into_iter().filter_map(Bdt::deployable).collect(),
+            classifiers,
         };
-        handlebars.render_to_write("template", &bdtlist, &mut writer)?;
+        handlebars.render_to_write("template", &bdtlist, writer)?;
 
         Ok(())
     }
 }
 
+impl BdtSink for SqliteAdapter {
+    /// Writes load script without classifiers
+    fn write(&self, tables: Vec<Bdt>, location: &str) -> Result<(), Box<dyn Error>> {
+        SqliteAdapter::write_bdt(
+            tables,
+            Vec::new(),
+            location,
+            &self.date_format,
+            &self.templates,
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use crate::csv_adapter::CsvReader;
+    use crate::json_adapter::JsonAdapter;
 
     use super::*;
 
+    #[allow(mismatched_lifetime_syntaxes)]
     fn setup(source: &str) -> Handlebars {
         let mut handlebars = Handlebars::new();
         handlebars
@@ -107,22 +202,99 @@ mod tests {
     }
 
     #[test]
-    fn give_text_when_value() {
-        let source = r#"{{some isvalue ~}}"#;
+    fn literal_helper_test() {
+        let source = r#"{{q text}},{{q num}},{{q flag}},{{q missing}}"#;
         let mut handlebars = setup(source);
-        handlebars.register_helper("some", Box::new(some_helper));
-        let mut data = HashMap::new();
-        data.insert("isvalue", "other");
-        assert_eq!(handlebars.render("testing", &data).unwrap(), "TEXT");
+        handlebars.register_helper("q", Box::new(literal_helper));
+        let data = serde_json::json!({"text": "Driver's", "num": 4, "flag": true});
+        assert_eq!(
+            handlebars.render("testing", &data).unwrap(),
+            "'Driver''s',4,'Y',NULL"
+        );
     }
 
     #[test]
-    fn give_no_text_when_value() {
-        let source = r#"{{some isvalue ~}}"#;
+    fn date_literal_helper_test() {
+        let source = r#"{{qdate from}},{{qdate to}}"#;
         let mut handlebars = setup(source);
-        handlebars.register_helper("some", Box::new(some_helper));
-        let mut data = HashMap::new();
-        data.insert("noValue", "other");
-        assert_eq!(handlebars.render("testing", &data).unwrap(), "");
+        handlebars.register_helper("qdate", Box::new(date_literal_helper));
+        let data = serde_json::json!({"from": "24.12.2020", "to": null});
+        assert_eq!(
+            handlebars.render("testing", &data).unwrap(),
+            "'2020-12-24',NULL"
+        );
+    }
+
+    #[test]
+    fn date_literal_helper_format_test() {
+        let mut handlebars = setup("{{qdate from}}");
+        handlebars.register_helper("qdate", Box::new(date_literal_helper));
+        let data = serde_json::json!({"from": "24.12.2020", "date_format": "%d/%m/%Y"});
+        assert_eq!(handlebars.render("testing", &data).unwrap(), "'24/12/2020'");
+        let data = serde_json::json!({"from": "2020-12-24"});
+        assert!(handlebars.render("testing", &data).is_err());
+    }
+
+    #[test]
+    fn metadata_tables_test() -> Result<(), Box<dyn Error>> {
+        let tables = JsonAdapter::read_bdt("./data/TT/TT.json")?;
+        let classifiers: Vec<ClassifierRecord> =
+            CsvReader::new(String::from("./data/TT/classifiers.csv"))?.collect();
+        let mut out = Vec::new();
+        SqliteAdapter::render(tables, classifiers, FORMAT, &Templates::default(), &mut out)?;
+        let script = String::from_utf8(out)?;
+        assert!(script.contains("CREATE TABLE bdt_classifiers"));
+        assert!(script.contains(
+            "INSERT INTO bdt_tables VALUES ('TT_COMBINED_POLICY_DISCOUNT', 'skipData', NULL, NULL);"
+        ));
+        assert!(script.contains("INSERT INTO bdt_table_names VALUES ('TT_FUEL_TYPE_REGITRA_MAP', 'LV', 'TT Fuel type Regitra map', '', '');"));
+        assert!(script.contains("INSERT INTO bdt_columns VALUES ('TT_COMBINED_POLICY_DISCOUNT', 'POLICY_TYPE', 'Policy type', 'CDF1_ID', 'Cdf', 'AGR_TYPE', 'icprefix=TT0', 1, 'Y', '', '');"));
+        assert!(script.contains("'DIESEL', 'D', NULL, NULL, 2, 'Driver''s choice');"));
+        assert!(!script.contains("'TT_CONFIG'"));
+        Ok(())
+    }
+
+    #[test]
+    fn validity_helper_test() {
+        let source = r#"{{validity columns "'now'"}}"#;
+        let mut handlebars = setup(source);
+        handlebars.register_helper("validity", Box::new(validity_helper));
+        let dated = serde_json::json!({"columns": [
+            {"name": "START", "ref_code": "VALID_FROM"},
+            {"name": "PERCENT", "ref_code": "NUM1"},
+            {"name": "END", "ref_code": "VALID_TO"}
+        ]});
+        assert_eq!(
+            handlebars.render("testing", &dated).unwrap(),
+            "(START IS NULL OR START = '' OR date(START) <= date('now')) AND (END IS NULL OR END = '' OR date(END) >= date('now'))"
+        );
+        let undated = serde_json::json!({"columns": [{"name": "PERCENT", "ref_code": "NUM1"}]});
+        assert_eq!(handlebars.render("testing", &undated).unwrap(), "1 = 1");
+    }
+
+    #[test]
+    fn effective_dated_views_test() -> Result<(), Box<dyn Error>> {
+        let tables = JsonAdapter::read_bdt("./data/TT/TT.json")?;
+        let mut out = Vec::new();
+        SqliteAdapter::render(tables, Vec::new(), FORMAT, &Templates::default(), &mut out)?;
+        let script = String::from_utf8(out)?;
+        assert!(script.contains("CREATE VIEW TT_FUEL_TYPE_REGITRA_MAP_CURRENT AS"));
+        assert!(script.contains("CREATE VIEW TT_FUEL_TYPE_REGITRA_MAP_AS_OF AS"));
+        assert!(script.contains(
+            "create index INDEX_TT_COMBINED_POLICY_DISCOUNT_KEY ON TT_COMBINED_POLICY_DISCOUNT (POLICY_TYPE, VALID_FROM, VALID_TO);"
+        ));
+        assert!(!script.contains("INDEX_TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE"));
+        assert!(!script.contains("INDEX_TT_COMBINED_POLICY_DISCOUNT_PERCENT"));
+        Ok(())
+    }
+
+    #[test]
+    fn no_classifier_table_without_classifiers() -> Result<(), Box<dyn Error>> {
+        let tables = JsonAdapter::read_bdt("./data/TT/TT.json")?;
+        let mut out = Vec::new();
+        SqliteAdapter::render(tables, Vec::new(), FORMAT, &Templates::default(), &mut out)?;
+        let script = String::from_utf8(out)?;
+        assert!(!script.contains("bdt_classifiers"));
+        Ok(())
     }
 }
@@ -0,0 +1,235 @@
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use handlebars::Handlebars;
+use serde::Serialize;
+
+use crate::bdt::Bdt;
+use crate::format::number_locale::NumberLocale;
+
+pub mod helpers;
+
+/// Built-in templates by file name, embedded at compile time. Names starting with `_`
+/// are partials shared by Oracle templates, like `{{> cdf_ic bdt=bdt}}` for `_cdf_ic.hbs`.
+pub const BUILTIN: [(&str, &str); 11] = [
+    ("_cdf_ic.hbs", include_str!("../render/_cdf_ic.hbs")),
+    ("_cdflng.hbs", include_str!("../render/_cdflng.hbs")),
+    ("_ctb_column.hbs", include_str!("../render/_ctb_column.hbs")),
+    ("_ctb_data.hbs", include_str!("../render/_ctb_data.hbs")),
+    ("bdtlist.hbs", include_str!("../render/bdtlist.hbs")),
+    ("merge.hbs", include_str!("../render/merge.hbs")),
+    ("rollback.hbs", include_str!("../render/rollback.hbs")),
+    ("install.hbs", include_str!("../render/install.hbs")),
+    ("liquibase.hbs", include_str!("../render/liquibase.hbs")),
+    ("sqlite.hbs", include_str!("../render/sqlite.hbs")),
+    ("postgres.hbs", include_str!("../render/postgres.hbs")),
+];
+
+/// Built-in template text by file name
+pub fn builtin(name: &str) -> Option<&'static str> {
+    BUILTIN
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(_, template)| *template)
+}
+
+/// Where adapter templates come from, built-in ones are used unless overridden
+#[derive(Clone, Debug, Default)]
+pub struct Templates {
+    /// Template file used instead of the adapter template
+    pub file: Option<PathBuf>,
+    /// Directory with templates named like built-in ones, missing ones are built-in
+    pub dir: Option<PathBuf>,
+}
+
+impl Templates {
+    /// Template text and its origin for messages
+    pub fn load(&self, name: &str) -> Result<(String, String), Box<dyn Error>> {
+        let file = self
+            .file
+            .clone()
+            .or_else(|| self.dir.as_ref().map(|dir| dir.join(name)))
+            .filter(|file| self.file.is_some() || file.exists());
+        match file {
+            Some(file) => read(&file),
+            None => builtin(name)
+                .map(|text| (text.to_string(), format!("built-in {}", name)))
+                .ok_or_else(|| format!("no built-in template {}", name).into()),
+        }
+    }
+
+    /// Registers template as `template`, with partials
+    pub fn register(&self, handlebars: &mut Handlebars, name: &str) -> Result<(), Box<dyn Error>> {
+        self.register_partials(handlebars)?;
+        let (text, origin) = self.load(name)?;
+        register_text(handlebars, text, &origin)
+    }
+
+    /// Registers template file as `template`, for templates not replacing built-in ones,
+    /// with built-in partials
+    pub fn register_file(handlebars: &mut Handlebars, file: &Path) -> Result<(), Box<dyn Error>> {
+        Templates::default().register_partials(handlebars)?;
+        let (text, origin) = read(file)?;
+        register_text(handlebars, text, &origin)
+    }
+
+    /// Registers partials from template directory or built-in ones, `--template` file
+    /// replaces adapter template only
+    fn register_partials(&self, handlebars: &mut Handlebars) -> Result<(), Box<dyn Error>> {
+        let partials = Templates {
+            file: None,
+            dir: self.dir.clone(),
+        };
+        for (file, _) in BUILTIN.iter() {
+            if let Some(name) = file.strip_prefix('_').and_then(|n| n.strip_suffix(".hbs")) {
+                let (text, origin) = partials.load(file)?;
+                handlebars
+                    .register_partial(name, text)
+                    .map_err(|e| format!("template {}: {}", origin, e))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Template text and file name as its origin
+fn read(file: &Path) -> Result<(String, String), Box<dyn Error>> {
+    let origin = file.display().to_string();
+    let text =
+        fs::read_to_string(file).map_err(|e| format!("can not read template {}: {}", origin, e))?;
+    Ok((text, origin))
+}
+
+fn register_text(
+    handlebars: &mut Handlebars,
+    text: String,
+    origin: &str,
+) -> Result<(), Box<dyn Error>> {
+    handlebars
+        .register_template_string("template", text)
+        .map_err(|e| format!("template {}: {}", origin, e))?;
+    Ok(())
+}
+
+/// Writes built-in templates to directory, existing files are kept unless `force`
+pub fn dump(dir: &Path, force: bool) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    if !force {
+        if let Some(file) = BUILTIN
+            .iter()
+            .map(|(name, _)| dir.join(name))
+            .find(|file| file.exists())
+        {
+            return Err(format!("{} exists, use --force to overwrite", file.display()).into());
+        }
+    }
+    fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    for (name, template) in BUILTIN {
+        let file = dir.join(name);
+        fs::write(&file, template)?;
+        written.push(file);
+    }
+    Ok(written)
+}
+
+#[derive(Debug, Serialize)]
+struct Bundle {
+    date_format: Option<String>,
+    decimal_separator: Option<char>,
+    tables: Vec<Bdt>,
+}
+
+/// Renders custom template with all [`helpers`], output is not HTML escaped. Dates and
+/// numbers are formatted with given format and locale, as in the bundle when not given.
+pub fn render<W: Write>(
+    template: &Path,
+    tables: Vec<Bdt>,
+    date_format: Option<String>,
+    numbers: Option<NumberLocale>,
+    writer: W,
+) -> Result<(), Box<dyn Error>> {
+    let mut handlebars = Handlebars::new();
+    handlebars.register_escape_fn(handlebars::no_escape);
+    helpers::register(&mut handlebars);
+    Templates::register_file(&mut handlebars, template)?;
+    let bundle = Bundle {
+        date_format,
+        decimal_separator: numbers.map(|numbers| numbers.decimal_separator()),
+        tables,
+    };
+    handlebars.render_to_write("template", &bundle, writer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_builtin_and_overrides() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!("bdt_template_{}", std::process::id()));
+        let written = dump(&dir, false)?;
+        assert_eq!(written.len(), BUILTIN.len());
+        assert!(dump(&dir, false).is_err());
+        fs::write(dir.join("sqlite.hbs"), "custom")?;
+        fs::remove_file(dir.join("postgres.hbs"))?;
+        assert!(dump(&dir, false).is_err());
+        assert!(
+            !dir.join("postgres.hbs").exists(),
+            "nothing written when any file exists"
+        );
+
+        let templates = Templates {
+            file: None,
+            dir: Some(dir.clone()),
+        };
+        assert_eq!(templates.load("sqlite.hbs")?.0, "custom");
+        let (text, origin) = templates.load("postgres.hbs")?;
+        assert_eq!(text, builtin("postgres.hbs").unwrap());
+        assert_eq!(origin, "built-in postgres.hbs");
+
+        fs::write(dir.join("_cdf_ic.hbs"), "custom {{bdt}}")?;
+        fs::write(dir.join("sqlite.hbs"), "{{> cdf_ic bdt=ic}}")?;
+        let mut handlebars = Handlebars::new();
+        templates.register(&mut handlebars, "sqlite.hbs")?;
+        let data = serde_json::json!({ "ic": "TT01" });
+        assert_eq!(handlebars.render("template", &data)?, "custom TT01");
+
+        let templates = Templates {
+            file: Some(dir.join("missing.hbs")),
+            dir: None,
+        };
+        assert!(templates.load("sqlite.hbs").is_err());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn render_custom_template() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!("bdt_render_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let template = dir.join("render.hbs");
+        fs::write(
+            &template,
+            "{{#each tables}}{{#if (eq ic \"TT_CONFIG\")}}{{name_in names \"LV\"}} <{{date valid_from}}>:\
+             {{#each (key_columns columns)}} {{name}}{{/each}}{{/if}}{{/each}}",
+        )?;
+        let tables = crate::json_adapter::JsonAdapter::read_bdt("./data/TT/TT.json")?;
+        let mut out = Vec::new();
+        render(
+            &template,
+            tables,
+            Some("%Y-%m-%d".to_string()),
+            None,
+            &mut out,
+        )?;
+        fs::remove_dir_all(&dir)?;
+        assert_eq!(
+            String::from_utf8(out)?,
+            "TT Konfigurācijas parametri <2020-12-24>: CONFIG_TYPE CONFIG_VALUE CONFIG_NUM_VALUE"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,737 @@
+//! Helpers available to templates. Script writers register the ones they use,
+//! [`register`] adds the whole set for custom templates of the `render` command.
+
+use chrono::NaiveDate;
+use handlebars::{Context, Handlebars, Helper, Output, RenderContext, RenderError};
+use serde_json::Value;
+
+use crate::format::lv_date_format;
+
+/// Registers all helpers of this module
+pub fn register(handlebars: &mut Handlebars) {
+    handlebars.register_helper("df", Box::new(data_field_helper));
+    handlebars.register_helper("yn", Box::new(yn_helper));
+    handlebars.register_helper("last", Box::new(array_last_comma));
+    handlebars.register_helper("some", Box::new(some_helper));
+    handlebars.register_helper("date", Box::new(date_helper));
+    handlebars.register_helper("name_in", Box::new(name_in_helper));
+    handlebars.register_helper("escape_sql", Box::new(escape_sql_helper));
+    handlebars.register_helper("sql", Box::new(oracle_literal_helper));
+    handlebars.register_helper("escape_xml", Box::new(escape_xml_helper));
+    handlebars.register_helper("escape_csv", Box::new(escape_csv_helper));
+    handlebars.register_helper("column", Box::new(column_helper));
+    handlebars.register_helper("key_columns", Box::new(key_columns_helper));
+    handlebars.register_helper("has_key", Box::new(has_key));
+    handlebars.register_helper("keys", Box::new(key_names_helper));
+}
+
+/// Reformats BDT date to `format`, else to `date_format` given in template data, if any
+pub(crate) fn format_date(
+    ctx: &Context,
+    date: &str,
+    format: Option<&str>,
+) -> Result<String, RenderError> {
+    let format = format.or_else(|| ctx.data().get("date_format").and_then(|v| v.as_str()));
+    match format {
+        Some(format) => NaiveDate::parse_from_str(date, lv_date_format::FORMAT)
+            .map(|date| date.format(format).to_string())
+            .map_err(|e| RenderError::new(format!("Wrong date {}: {}", date, e))),
+        None => Ok(date.to_string()),
+    }
+}
+
+/// Writes exact decimal number with `decimal_separator` given in template data, if any
+fn format_number(ctx: &Context, num: &str) -> String {
+    match ctx.data().get("decimal_separator").and_then(|v| v.as_str()) {
+        Some(separator) => num.replace('.', separator),
+        None => num.to_string(),
+    }
+}
+
+/// Text of param, missing and null values are empty
+fn text_param(h: &Helper, index: usize) -> String {
+    match h.param(index).map(|v| v.value()) {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(text)) => text.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+/// `{{date valid_from}}` or `{{date valid_from "%Y-%m-%d"}}`
+pub fn date_helper(
+    h: &Helper,
+    _: &Handlebars,
+    ctx: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> Result<(), RenderError> {
+    if let Some(date) = h.param(0).and_then(|v| v.value().as_str()) {
+        let format = h.param(1).and_then(|v| v.value().as_str());
+        write!(out, "{}", format_date(ctx, date, format)?)?;
+    }
+    Ok(())
+}
+
+/// `{{df values "NUM1"}}` writes value of row column with given reference code
+pub fn data_field_helper(
+    h: &Helper,
+    _: &Handlebars,
+    ctx: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> Result<(), RenderError> {
+    let field_value = h
+        .param(0)
+        .and_then(|v| v.value().as_array())
+        .ok_or(RenderError::new(
+            "Param 0 with field value object is required for data field helper.",
+        ))?;
+    let field_ref = h
+        .param(1)
+        .and_then(|v| v.value().as_str())
+        .ok_or(RenderError::new(
+            "Param 1 with field ref string is required for data field helper.",
+        ))?;
+
+    for value in field_value.iter() {
+        let test_code = value["ref_code"].as_str();
+        if let Some(v) = test_code {
+            if v == field_ref {
+                let print_value = value["value"].as_object();
+                if let Some(pv) = print_value {
+                    if let Some(cdf) = pv.get("Cdf") {
+                        write!(out, "{}", cdf.as_str().get_or_insert(""))?
+                    }
+                    if let Some(text) = pv.get("Text") {
+                        write!(out, "{}", text.as_str().get_or_insert(""))?
+                    }
+                    if let Some(num) = pv.get("Num") {
+                        if num.is_number() {
+                            write!(out, "{}", format_number(ctx, &num.to_string()))?
+                        }
+                    }
+                    if let Some(date) = pv.get("Date") {
+                        if let Some(date_val) = date.as_str() {
+                            write!(out, "{}", format_date(ctx, date_val, None)?)?
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `{{yn is_key}}` writes `Y` or `N`
+pub fn yn_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> Result<(), RenderError> {
+    let param = h
+        .param(0)
+        .and_then(|v| v.value().as_bool())
+        .ok_or(RenderError::new(
+            "Param 0 with bool type is required for yn helper.",
+        ))?;
+    if param {
+        write!(out, "Y")?;
+    } else {
+        write!(out, "N")?;
+    }
+    Ok(())
+}
+
+// handlebars_helper!(last: |array: , index: usize| if array.len() == index { ",".to_string() } else { "".to_string()});
+
+/// `{{last columns @index}}` writes comma unless at the last item
+pub fn array_last_comma(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> Result<(), RenderError> {
+    let length = h
+        .param(0)
+        .as_ref()
+        .and_then(|v| v.value().as_array())
+        .map(|arr| arr.len())
+        .ok_or(RenderError::new(
+            "Param 0 with 'array' type is required for array_last_comma helper",
+        ))?;
+
+    let index = h
+        .param(1)
+        .as_ref()
+        .and_then(|v| v.value().as_u64())
+        .ok_or(RenderError::new(
+            "Param 1 with 'usize' type is required for array_last_comma helper",
+        ))?;
+
+    if length as u64 != index + 1 {
+        write!(out, ",")?;
+    } else {
+        write!(out, "")?;
+    }
+
+    Ok(())
+}
+
+/// `{{some value}}` writes `TEXT` when value is present
+pub fn some_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> Result<(), RenderError> {
+    let param = h.param(0);
+    match param {
+        Some(value) => {
+            if value.is_value_missing() {
+                write!(out, "")?;
+            } else {
+                write!(out, "TEXT")?;
+            }
+        }
+        _ => {
+            write!(out, "")?;
+        }
+    }
+    Ok(())
+}
+
+/// `{{name_in names "LV"}}` or `{{name_in names "LV" "print_name"}}` writes table name in
+/// language, falling back to EN
+pub fn name_in_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> Result<(), RenderError> {
+    let names = h
+        .param(0)
+        .map(|v| v.value())
+        .and_then(|names| names.get("names").unwrap_or(names).as_array())
+        .ok_or(RenderError::new(
+            "Param 0 with table names is required for name_in helper.",
+        ))?;
+    let lang = h
+        .param(1)
+        .and_then(|v| v.value().as_str())
+        .ok_or(RenderError::new(
+            "Param 1 with language is required for name_in helper.",
+        ))?;
+    let field = h
+        .param(2)
+        .and_then(|v| v.value().as_str())
+        .unwrap_or("name");
+    let by_lang = |lang: &str| {
+        names
+            .iter()
+            .find(|name| name["lang"].as_str() == Some(lang))
+    };
+    if let Some(name) = by_lang(lang).or_else(|| by_lang("EN")) {
+        write!(out, "{}", name[field].as_str().unwrap_or_default())?;
+    }
+    Ok(())
+}
+
+/// `{{escape_sql text}}` doubles single quotes for use inside SQL string literal
+pub fn escape_sql_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> Result<(), RenderError> {
+    write!(out, "{}", text_param(h, 0).replace('\'', "''"))?;
+    Ok(())
+}
+
+/// Characters per literal chunk, keeps literals within 4000 bytes for any UTF-8 text
+const LITERAL_CHUNK: usize = 1000;
+
+/// Quoted Oracle string literal of text, safe in SQL*Plus scripts regardless of
+/// `set define`: quotes are doubled, `&` and control characters except tab become
+/// `chr(n)`, so no line break is inside quotes, and long text is split into chunks
+/// joined with `||`. Parts go on new lines every 1000 characters to keep
+/// lines within SQL*Plus limit. A part of quotes takes up to 2000 characters, so scripts
+/// put each literal on its own line. Empty text is `''`, which Oracle treats as NULL.
+pub fn oracle_literal(text: &str) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut literal = String::new();
+    let mut chars = 0;
+    for c in text.chars() {
+        if c == '&' || (c.is_control() && c != '\t') {
+            if !literal.is_empty() {
+                parts.push(format!("'{}'", literal));
+                literal.clear();
+                chars = 0;
+            }
+            parts.push(format!("chr({})", c as u32));
+            continue;
+        }
+        if chars == LITERAL_CHUNK {
+            parts.push(format!("'{}'", literal));
+            literal.clear();
+            chars = 0;
+        }
+        if c == '\'' {
+            literal.push('\'');
+        }
+        literal.push(c);
+        chars += 1;
+    }
+    if !literal.is_empty() || parts.is_empty() {
+        parts.push(format!("'{}'", literal));
+    }
+    let mut result = String::new();
+    let mut line = 0;
+    for (index, part) in parts.iter().enumerate() {
+        let width = part.chars().count();
+        if index > 0 {
+            if line + width > LITERAL_CHUNK {
+                result.push('\n');
+                line = 0;
+            }
+            result.push_str("||");
+            line += 2;
+        }
+        result.push_str(part);
+        line += width;
+    }
+    result
+}
+
+/// `{{sql value}}` writes value as Oracle literal, missing values become `''`
+pub fn oracle_literal_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> Result<(), RenderError> {
+    let text = match h.param(0).map(|v| v.value()) {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(text)) => text.clone(),
+        Some(value) => value.to_string(),
+    };
+    write!(out, "{}", oracle_literal(&text))?;
+    Ok(())
+}
+
+/// Escapes XML markup characters, for text and attribute values
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// `{{escape_xml text}}` escapes XML markup characters
+pub fn escape_xml_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> Result<(), RenderError> {
+    write!(out, "{}", escape_xml(&text_param(h, 0)))?;
+    Ok(())
+}
+
+/// `{{escape_csv text}}` or `{{escape_csv text ";"}}` quotes CSV field when needed
+pub fn escape_csv_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> Result<(), RenderError> {
+    let text = text_param(h, 0);
+    let delimiter = h.param(1).and_then(|v| v.value().as_str()).unwrap_or(",");
+    if text.contains(delimiter) || text.contains(['"', '\n', '\r']) {
+        write!(out, "\"{}\"", text.replace('"', "\"\""))?;
+    } else {
+        write!(out, "{}", text)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn columns_param<'a>(
+    h: &'a Helper,
+    helper: &str,
+) -> Result<&'a Vec<Value>, RenderError> {
+    h.param(0)
+        .and_then(|v| v.value().as_array())
+        .ok_or(RenderError::new(format!(
+            "Param 0 with columns array is required for {} helper.",
+            helper
+        )))
+}
+
+fn is_key(column: &Value) -> bool {
+    column["is_key"].as_bool().unwrap_or(false)
+}
+
+pub use generated::{column_helper, has_key, key_columns_helper};
+
+/// Helpers declared with `handlebars_helper!`, whose structs can not carry docs
+#[allow(missing_docs)]
+mod generated {
+    use handlebars::handlebars_helper;
+    use serde_json::Value;
+
+    use super::is_key;
+
+    // `{{#with (column columns "CONFIG_TYPE")}}{{title}}{{/with}}`, by name or reference code
+    handlebars_helper!(column_helper: |columns: array, name: str| columns
+        .iter()
+        .find(|column| ["name", "ref_code"]
+            .iter()
+            .any(|field| column[field].as_str().is_some_and(|n| n.eq_ignore_ascii_case(name))))
+        .cloned()
+        .unwrap_or(Value::Null));
+
+    // `{{#each (key_columns columns)}}{{name}}{{/each}}`
+    handlebars_helper!(key_columns_helper: |columns: array| Value::Array(columns
+        .iter()
+        .filter(|column| is_key(column))
+        .cloned()
+        .collect()));
+
+    handlebars_helper!(has_key: |columns: array| columns.iter().any(is_key));
+}
+
+/// `{{keys columns}}` writes comma separated names of key columns
+pub fn key_names_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> Result<(), RenderError> {
+    let columns = columns_param(h, "keys")?;
+    let names: Vec<&str> = columns
+        .iter()
+        .filter(|column| is_key(column))
+        .filter_map(|column| column["name"].as_str())
+        .collect();
+    write!(out, "{}", names.join(", "))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::bdt::column_value::ColumnValue;
+
+    use super::*;
+
+    #[allow(mismatched_lifetime_syntaxes)]
+    fn setup(source: &str) -> Handlebars {
+        let mut handlebars = Handlebars::new();
+        handlebars
+            .register_template_string("testing", source)
+            .unwrap();
+        handlebars
+    }
+
+    fn render(source: &str, data: Value) -> String {
+        let mut handlebars = setup(source);
+        handlebars.register_escape_fn(handlebars::no_escape);
+        register(&mut handlebars);
+        handlebars.render("testing", &data).unwrap()
+    }
+
+    #[test]
+    fn date_helper_test() {
+        let source = r#"{{date valid_from}}"#;
+        let mut handlebars = setup(source);
+        handlebars.register_helper("date", Box::new(date_helper));
+        let data = serde_json::json!({"valid_from": "01.09.2017", "date_format": "%Y-%m-%d"});
+        assert_eq!(handlebars.render("testing", &data).unwrap(), "2017-09-01");
+        let data = serde_json::json!({"valid_from": "01.09.2017"});
+        assert_eq!(handlebars.render("testing", &data).unwrap(), "01.09.2017");
+        let data = serde_json::json!({"valid_from": "01.09.2017"});
+        assert_eq!(
+            render(r#"{{date valid_from "%d/%m/%Y"}}"#, data),
+            "01/09/2017"
+        );
+    }
+
+    #[test]
+    fn data_field_helper_test_decimal_separator() {
+        let source = r#"{{df data "NUM1" ~}}"#;
+        let mut handlebars = setup(source);
+        handlebars.register_helper("df", Box::new(data_field_helper));
+        let data = serde_json::json!({
+            "decimal_separator": ",",
+            "data": [{"name": "RATE", "ref_code": "NUM1", "value": {"Num": 0.25}}]
+        });
+        assert_eq!(handlebars.render("testing", &data).unwrap(), "0,25");
+    }
+
+    #[test]
+    fn yn_helper_test() {
+        let source = "{{yn is_key ~}}";
+        let mut handlebars = setup(source);
+        handlebars.register_helper("yn", Box::new(yn_helper));
+
+        let mut is_key_data: HashMap<&str, bool> = HashMap::new();
+        is_key_data.insert("is_key", true);
+        assert_eq!(handlebars.render("testing", &is_key_data).unwrap(), "Y");
+        let mut no_key_data: HashMap<&str, bool> = HashMap::new();
+        no_key_data.insert("is_key", false);
+        assert_eq!(handlebars.render("testing", &no_key_data).unwrap(), "N");
+    }
+
+    #[test]
+    fn data_field_helper_test_cdf() {
+        let source = r#"{{df data "CDF2_ID" ~}}"#;
+        let data_json = r#"[{
+                "name": "AGE_FROM",
+                "ref_code": "NUM1",
+                "value": {"Num": 0.0}
+            },{
+                "name": "AGE_TILL",
+                "ref_code": "NUM2",
+                "value": {"Num": 4.0}
+            },{
+                "name": "READ_ONLY",
+                "ref_code": "CDF1_ID",
+                "value": {"Cdf": "Y"}
+            },{
+                "name": "DEFAULT_FIELD_VALUE",
+                "ref_code": "CDF2_ID",
+                "value": {"Cdf": "Y"}
+            }]"#;
+        let values: Vec<ColumnValue> = serde_json::from_str(data_json).unwrap();
+
+        let mut handlebars = setup(source);
+        handlebars.register_helper("df", Box::new(data_field_helper));
+
+        let mut column_data: HashMap<&str, Vec<ColumnValue>> = HashMap::new();
+        column_data.insert("data", values);
+        assert_eq!(handlebars.render("testing", &column_data).unwrap(), "Y");
+    }
+
+    #[test]
+    fn data_field_helper_test_num() {
+        let source = r#"{{df data "NUM2" ~}}"#;
+        let data_json = r#"[{
+                "name": "AGE_FROM",
+                "ref_code": "NUM1",
+                "value": {"Num": 0.0}
+            },{
+                "name": "AGE_TILL",
+                "ref_code": "NUM2",
+                "value": {"Num": 4.0}
+            },{
+                "name": "READ_ONLY",
+                "ref_code": "CDF1_ID",
+                "value": {"Cdf": "Y"}
+            },{
+                "name": "DEFAULT_FIELD_VALUE",
+                "ref_code": "CDF2_ID",
+                "value": {"Cdf": "Y"}
+            }]"#;
+        let values: Vec<ColumnValue> = serde_json::from_str(data_json).unwrap();
+
+        let mut handlebars = setup(source);
+        handlebars.register_helper("df", Box::new(data_field_helper));
+
+        let mut column_data: HashMap<&str, Vec<ColumnValue>> = HashMap::new();
+        column_data.insert("data", values);
+        assert_eq!(handlebars.render("testing", &column_data).unwrap(), "4.0");
+    }
+
+    #[test]
+    fn data_field_helper_keeps_num_scale() {
+        let values: Vec<ColumnValue> = serde_json::from_str(
+            r#"[{"name": "RATE", "ref_code": "NUM1", "value": {"Num": 1.150}}]"#,
+        )
+        .unwrap();
+        let mut handlebars = setup(r#"{{df data "NUM1" ~}}"#);
+        handlebars.register_helper("df", Box::new(data_field_helper));
+        let column_data = HashMap::from([("data", values)]);
+        assert_eq!(handlebars.render("testing", &column_data).unwrap(), "1.150");
+    }
+
+    #[test]
+    fn data_field_helper_test_date() {
+        let source = r#"{{df data "VALID_TO" ~}}"#;
+        let data_json = r#"[{
+                "name": "AGE_FROM",
+                "ref_code": "NUM1",
+                "value": {"Num": 0.0}
+            },{
+                "name": "VALID_TO",
+                "ref_code": "VALID_TO",
+                "value": {
+                    "Date": "01.09.2017"
+                }
+            },{
+                "name": "READ_ONLY",
+                "ref_code": "CDF1_ID",
+                "value": {"Cdf": "Y"}
+            },{
+                "name": "DEFAULT_FIELD_VALUE",
+                "ref_code": "CDF2_ID",
+                "value": {"Cdf": "Y"}
+            }]"#;
+        let values: Vec<ColumnValue> = serde_json::from_str(data_json).unwrap();
+
+        let mut handlebars = setup(source);
+        handlebars.register_helper("df", Box::new(data_field_helper));
+
+        let mut column_data: HashMap<&str, Vec<ColumnValue>> = HashMap::new();
+        column_data.insert("data", values);
+        assert_eq!(
+            handlebars.render("testing", &column_data).unwrap(),
+            "01.09.2017"
+        );
+    }
+
+    #[test]
+    fn give_text_when_value() {
+        let source = r#"{{some isvalue ~}}"#;
+        let mut handlebars = setup(source);
+        handlebars.register_helper("some", Box::new(some_helper));
+        let mut data = HashMap::new();
+        data.insert("isvalue", "other");
+        assert_eq!(handlebars.render("testing", &data).unwrap(), "TEXT");
+    }
+
+    #[test]
+    fn key_columns_helper_test() {
+        let source = r#"{{#if (has_key columns)}}{{keys columns}}{{else}}none{{/if}}"#;
+        let mut handlebars = setup(source);
+        handlebars.register_helper("has_key", Box::new(has_key));
+        handlebars.register_helper("keys", Box::new(key_names_helper));
+        let keys = serde_json::json!({"columns": [
+            {"name": "POLICY_TYPE", "is_key": true},
+            {"name": "PERCENT", "is_key": false},
+            {"name": "VALID_FROM", "is_key": true}
+        ]});
+        assert_eq!(
+            handlebars.render("testing", &keys).unwrap(),
+            "POLICY_TYPE, VALID_FROM"
+        );
+        let no_keys = serde_json::json!({"columns": [{"name": "PERCENT", "is_key": false}]});
+        assert_eq!(handlebars.render("testing", &no_keys).unwrap(), "none");
+    }
+
+    #[test]
+    fn give_no_text_when_value() {
+        let source = r#"{{some isvalue ~}}"#;
+        let mut handlebars = setup(source);
+        handlebars.register_helper("some", Box::new(some_helper));
+        let mut data = HashMap::new();
+        data.insert("noValue", "other");
+        assert_eq!(handlebars.render("testing", &data).unwrap(), "");
+    }
+
+    #[test]
+    fn name_in_helper_test() {
+        let data = serde_json::json!({"names": {"names": [
+            {"lang": "EN", "name": "Age", "print_name": "Vehicle age"},
+            {"lang": "LV", "name": "Vecums", "print_name": "Auto vecums"}
+        ]}});
+        let source =
+            r#"{{name_in names "LV"}}/{{name_in names "LV" "print_name"}}/{{name_in names "PL"}}"#;
+        assert_eq!(render(source, data), "Vecums/Auto vecums/Age");
+    }
+
+    #[test]
+    fn escape_helpers_test() {
+        let data = serde_json::json!({"text": "Driver's \"<choice>\" & more", "num": 1.5});
+        assert_eq!(
+            render("{{escape_sql text}}", data.clone()),
+            "Driver''s \"<choice>\" & more"
+        );
+        assert_eq!(
+            render("{{escape_xml text}}", data.clone()),
+            "Driver&apos;s &quot;&lt;choice&gt;&quot; &amp; more"
+        );
+        assert_eq!(
+            render("{{escape_csv text}}", data.clone()),
+            "\"Driver's \"\"<choice>\"\" & more\""
+        );
+        assert_eq!(
+            render(r#"{{escape_csv num ";"}},{{escape_csv missing}}"#, data),
+            "1.5,"
+        );
+        let data = serde_json::json!({"text": "a;b"});
+        assert_eq!(render("{{escape_csv text}}", data.clone()), "a;b");
+        assert_eq!(render(r#"{{escape_csv text ";"}}"#, data), "\"a;b\"");
+    }
+
+    #[test]
+    fn column_lookup_and_key_iteration() {
+        let data = serde_json::json!({"columns": [
+            {"name": "POLICY_TYPE", "ref_code": "CDF1_ID", "title": "Policy type", "is_key": true},
+            {"name": "PERCENT", "ref_code": "NUM1", "title": "Percent", "is_key": false},
+            {"name": "VALID_FROM", "ref_code": "VALID_FROM", "title": "Valid from", "is_key": true}
+        ]});
+        let source = r#"{{#with (column columns "percent")}}{{title}}{{/with}},{{#with (column columns "CDF1_ID")}}{{name}}{{/with}}"#;
+        assert_eq!(render(source, data.clone()), "Percent,POLICY_TYPE");
+        let source =
+            r#"{{#each (key_columns columns)}}{{title}}{{#unless @last}}|{{/unless}}{{/each}}"#;
+        assert_eq!(render(source, data), "Policy type|Valid from");
+    }
+
+    #[test]
+    fn oracle_literal_quotes() {
+        assert_eq!(oracle_literal(""), "''");
+        assert_eq!(oracle_literal("Driver's"), "'Driver''s'");
+        assert_eq!(oracle_literal("''"), "''''''");
+        assert_eq!(oracle_literal("a\tb"), "'a\tb'");
+    }
+
+    #[test]
+    fn oracle_literal_line_breaks_and_ampersand() {
+        assert_eq!(oracle_literal("a\r\nb"), "'a'||chr(13)||chr(10)||'b'");
+        assert_eq!(oracle_literal("\n"), "chr(10)");
+        assert_eq!(
+            oracle_literal("begin\n/\nend"),
+            "'begin'||chr(10)||'/'||chr(10)||'end'"
+        );
+        assert_eq!(
+            oracle_literal("R&D &&x"),
+            "'R'||chr(38)||'D '||chr(38)||chr(38)||'x'"
+        );
+    }
+
+    #[test]
+    fn oracle_literal_long_text() {
+        let text = format!("{}'{}", "ā".repeat(999), "b".repeat(1500));
+        let literal = oracle_literal(&text);
+        let chunks: Vec<&str> = literal.split("'\n||'").collect();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0], format!("'{}''", "ā".repeat(999)));
+        assert_eq!(chunks[1], "b".repeat(1000));
+        assert_eq!(chunks[2], format!("{}'", "b".repeat(500)));
+
+        let literal = oracle_literal(&"a&".repeat(1000));
+        assert!(literal.lines().count() > 1);
+        assert!(literal
+            .lines()
+            .all(|line| line.chars().count() <= LITERAL_CHUNK));
+        assert!(literal.lines().skip(1).all(|line| line.starts_with("||")));
+    }
+}
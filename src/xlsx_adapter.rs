@@ -0,0 +1,126 @@
+use std::error::Error;
+use std::path::Path;
+
+use chrono::Datelike;
+use rust_decimal::Decimal;
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook, Worksheet};
+
+use crate::bdt::column_value::ColumnValueType;
+use crate::bdt::Bdt;
+use crate::bdt_io::BdtSink;
+
+/// Sheet names are limited to 31 characters
+const SHEET_NAME: usize = 31;
+
+/// Excel workbook per table for business users, with a header of column names
+pub struct XlsxAdapter {}
+
+/// Number as Excel double when it keeps all digits, Excel has no exact decimals
+fn excel_number(num: &Decimal) -> Option<f64> {
+    let number: f64 = num.to_string().parse().ok()?;
+    (number.to_string() == num.normalize().to_string()).then_some(number)
+}
+
+fn write_value(
+    sheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    value: &ColumnValueType,
+    date: &Format,
+) -> Result<(), Box<dyn Error>> {
+    match value {
+        ColumnValueType::Date(Some(day)) => {
+            let day =
+                ExcelDateTime::from_ymd(day.year() as u16, day.month() as u8, day.day() as u8)?;
+            sheet.write_datetime_with_format(row, col, &day, date)?;
+        }
+        ColumnValueType::Num(Some(num)) => match excel_number(num) {
+            Some(number) => {
+                sheet.write_number(row, col, number)?;
+            }
+            None => {
+                sheet.write_string(row, col, num.to_string())?;
+            }
+        },
+        ColumnValueType::Text(text) | ColumnValueType::Cdf(text) if !text.is_empty() => {
+            sheet.write_string(row, col, text)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+impl XlsxAdapter {
+    /// Writes each BDT to `<path>/<IC>.xlsx`, numbers with more digits than Excel keeps
+    /// are written as text
+    pub fn write_bdt(tables: Vec<Bdt>, path: &str) -> Result<(), Box<dyn Error>> {
+        for bdt in tables.into_iter().filter_map(Bdt::deployable) {
+            let mut workbook = XlsxAdapter::workbook(&bdt)?;
+            workbook.save(Path::new(path).join(format!("{}.xlsx", bdt.ic)))?;
+        }
+        Ok(())
+    }
+
+    fn workbook(bdt: &Bdt) -> Result<Workbook, Box<dyn Error>> {
+        let header = Format::new().set_bold();
+        let date = Format::new().set_num_format("yyyy-mm-dd");
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(bdt.ic.chars().take(SHEET_NAME).collect::<String>())?;
+        sheet.set_freeze_panes(1, 0)?;
+        for (col, column) in bdt.columns.iter().enumerate() {
+            let col = col as u16;
+            sheet.write_string_with_format(0, col, &column.name, &header)?;
+            for (row, values) in bdt.data.iter().enumerate() {
+                if let Some(value) = values
+                    .values
+                    .iter()
+                    .find(|value| value.ref_code == column.ref_code)
+                {
+                    write_value(sheet, row as u32 + 1, col, &value.value, &date)?;
+                }
+            }
+        }
+        Ok(workbook)
+    }
+}
+
+impl BdtSink for XlsxAdapter {
+    fn write(&self, tables: Vec<Bdt>, location: &str) -> Result<(), Box<dyn Error>> {
+        XlsxAdapter::write_bdt(tables, location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::json_adapter::JsonAdapter;
+
+    use super::*;
+
+    #[test]
+    fn numbers_keep_their_digits() {
+        let num = |text: &str| text.parse::<Decimal>().unwrap();
+        assert_eq!(excel_number(&num("9.9")), Some(9.9));
+        assert_eq!(excel_number(&num("30.00")), Some(30.0));
+        assert_eq!(excel_number(&num("0.1")), Some(0.1));
+        assert_eq!(excel_number(&num("12345678901234567.1")), None);
+    }
+
+    #[test]
+    fn workbook_per_deployed_table() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!("bdt_xlsx_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let tables = JsonAdapter::read_bdt("./data/TT/TT.json")?;
+        XlsxAdapter::write_bdt(tables, dir.to_str().unwrap())?;
+        let written = dir
+            .join("TT02_DEPRECIATION_CONFIG_BY_VEHICLE_AGE.xlsx")
+            .exists();
+        let skipped = dir.join("TT_CONFIG.xlsx").exists();
+        fs::remove_dir_all(&dir)?;
+        assert!(written);
+        assert!(!skipped);
+        Ok(())
+    }
+}